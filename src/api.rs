@@ -0,0 +1,142 @@
+use wasm_bindgen::prelude::*;
+
+use crate::bus::Mode;
+use crate::core::board::WrapConfig;
+use crate::core::bot::Personality;
+use crate::palette::Scheme;
+use crate::settings::{self, Settings, MAX_TICK_MS, MIN_TICK_MS};
+use crate::theme;
+use crate::with_game;
+
+/// The player-facing options as one typed object, for pages that would
+/// rather not parse `get_settings()`.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone)]
+pub struct GameConfig {
+    /// Milliseconds per tick, 40 to 400.
+    pub tick_ms: u32,
+    /// Whether snakes wrap across the left and right edges.
+    pub wrap_x: bool,
+    /// Whether snakes wrap across the top and bottom edges.
+    pub wrap_y: bool,
+    /// `"dark"`, `"light"`, `"retro"`, `"ocean"`, `"auto"` or the name of a
+    /// custom theme.
+    pub theme: String,
+    /// `"standard"`, or a palette for colour blindness: `"deuteranopia"`,
+    /// `"protanopia"` or `"tritanopia"`.
+    pub palette: String,
+    /// Master volume from 0 to 1.
+    pub volume: f64,
+    pub music_volume: f64,
+    pub sfx_volume: f64,
+    pub music: bool,
+    /// Seconds without input before a game pauses; 0 is off.
+    pub idle_timeout: u32,
+    pub debug_hud: bool,
+    pub stats_panel: bool,
+    /// Whether the theme's scenery drifts behind the board.
+    pub backdrop: bool,
+    /// Whether snakes glide between cells.
+    pub smooth: bool,
+    /// How the autopilot plays: `"greedy"`, `"cautious"`, `"blocker"` or
+    /// `"wanderer"`.
+    pub autopilot: String,
+}
+
+#[wasm_bindgen]
+impl GameConfig {
+    /// The default options.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> GameConfig {
+        GameConfig::from(&Settings::default())
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<&Settings> for GameConfig {
+    fn from(s: &Settings) -> Self {
+        Self {
+            tick_ms: s.tick_ms,
+            wrap_x: s.wrap.x,
+            wrap_y: s.wrap.y,
+            theme: s.theme.clone(),
+            palette: s.palette.name().to_string(),
+            volume: s.volume,
+            music_volume: s.music_volume,
+            sfx_volume: s.sfx_volume,
+            music: s.music,
+            idle_timeout: s.idle_timeout,
+            debug_hud: s.debug_hud,
+            stats_panel: s.stats_panel,
+            backdrop: s.backdrop,
+            smooth: s.smooth,
+            autopilot: s.autopilot.name().to_string(),
+        }
+    }
+}
+
+/// A snapshot of the game in progress.
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct GameStats {
+    pub mode: Mode,
+    /// This browser's score.
+    pub score: i32,
+    /// The other snake's score in versus play.
+    pub rival_score: Option<i32>,
+    pub tick: u32,
+    pub over: bool,
+    pub paused: bool,
+    /// The top score in the local table.
+    pub best: Option<i32>,
+}
+
+#[wasm_bindgen]
+pub fn get_config() -> GameConfig {
+    settings::with(|s| GameConfig::from(s))
+}
+
+/// Saves every option in `config` at once. Out-of-range numbers are
+/// clamped; an unknown theme, palette or autopilot is an error and changes
+/// nothing.
+#[wasm_bindgen]
+pub fn set_config(config: &GameConfig) -> Result<(), JsValue> {
+    if theme::find(&config.theme).is_none() {
+        return Err(JsValue::from_str("unknown theme"));
+    }
+    let palette =
+        Scheme::parse(&config.palette).ok_or_else(|| JsValue::from_str("unknown palette"))?;
+    let autopilot = Personality::parse(&config.autopilot)
+        .ok_or_else(|| JsValue::from_str("unknown personality"))?;
+    settings::update(|s| {
+        s.tick_ms = config.tick_ms.clamp(MIN_TICK_MS, MAX_TICK_MS);
+        s.wrap = WrapConfig {
+            x: config.wrap_x,
+            y: config.wrap_y,
+        };
+        s.theme = config.theme.clone();
+        s.palette = palette;
+        s.volume = config.volume.clamp(0.0, 1.0);
+        s.music_volume = config.music_volume.clamp(0.0, 1.0);
+        s.sfx_volume = config.sfx_volume.clamp(0.0, 1.0);
+        s.music = config.music;
+        s.idle_timeout = config.idle_timeout;
+        s.debug_hud = config.debug_hud;
+        s.stats_panel = config.stats_panel;
+        s.backdrop = config.backdrop;
+        s.smooth = config.smooth;
+        s.autopilot = autopilot;
+    });
+    Ok(())
+}
+
+/// The current game's state, or `undefined` before the game has started.
+#[wasm_bindgen]
+pub fn get_stats() -> Option<GameStats> {
+    with_game(|g| g.stats())
+}
@@ -0,0 +1,250 @@
+use std::cell::RefCell;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use web_sys::{
+    CanvasRenderingContext2d, FontFace, FontFaceLoadStatus, FontFaceSetLoadStatus, HtmlImageElement,
+};
+
+use crate::theme::Theme;
+
+// tries an asset gets before the game carries on without it
+const MAX_TRIES: u32 = 3;
+// how long one try may take before it counts as failed
+const TRY_MS: f64 = 8000.0;
+
+thread_local! {
+    static ASSETS: RefCell<Assets> = RefCell::new(Assets::default());
+}
+
+enum Source {
+    Image {
+        image: HtmlImageElement,
+        url: String,
+    },
+    /// A CSS font, as `set_font` takes it.
+    Font(String),
+    /// A web font from `url`, added to the page as `family`.
+    Face {
+        family: String,
+        url: String,
+        face: Option<FontFace>,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Status {
+    Loading,
+    Ready,
+    /// Given up on; the game uses the asset's fallback instead.
+    Failed,
+}
+
+struct Asset {
+    name: String,
+    source: Source,
+    status: Status,
+    tries: u32,
+    // when the current try began
+    started: f64,
+}
+
+impl Asset {
+    fn poll(&mut self, now: f64) {
+        if self.status != Status::Loading {
+            return;
+        }
+        let (done, broken) = match &self.source {
+            Source::Image { image, .. } => {
+                let done = image.complete();
+                (
+                    done && image.natural_width() > 0,
+                    done && image.natural_width() == 0,
+                )
+            }
+            // with nothing left loading, a font that still isn't there
+            // isn't coming
+            Source::Font(font) => match fonts() {
+                Some(fonts) => {
+                    let done = fonts.check(font).unwrap_or(true);
+                    (
+                        done,
+                        !done && fonts.status() == FontFaceSetLoadStatus::Loaded,
+                    )
+                }
+                None => (true, false),
+            },
+            Source::Face { face, .. } => {
+                let status = face.as_ref().map(FontFace::status);
+                (
+                    status == Some(FontFaceLoadStatus::Loaded),
+                    matches!(status, None | Some(FontFaceLoadStatus::Error)),
+                )
+            }
+        };
+        if done {
+            self.status = Status::Ready;
+        } else if broken || now - self.started >= TRY_MS {
+            self.retry(now);
+        }
+    }
+
+    fn retry(&mut self, now: f64) {
+        if self.tries >= MAX_TRIES {
+            self.status = Status::Failed;
+            return;
+        }
+        self.tries += 1;
+        self.started = now;
+        match &mut self.source {
+            Source::Image { image, url } => {
+                image.set_src("");
+                image.set_src(url);
+            }
+            Source::Font(font) => {
+                if let Some(fonts) = fonts() {
+                    let _ = fonts.load(font);
+                }
+            }
+            // a face that failed stays failed, so each try starts afresh
+            Source::Face { family, url, face } => *face = load_face(family, url),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Assets {
+    list: Vec<Asset>,
+    // whether readiness has been announced for what is loaded now
+    announced: bool,
+}
+
+/// What the page is told once every asset has loaded or been given up on.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Ready {
+    /// Names of the assets the game is using a fallback for.
+    pub failed: Vec<String>,
+}
+
+/// Starts loading the image at `url` as the asset `name`, replacing any
+/// asset of that name. Until it loads the image isn't drawn, so the
+/// caller's fallback shows.
+pub fn image(name: &str, url: &str, now: f64) -> Option<HtmlImageElement> {
+    let image = HtmlImageElement::new().ok()?;
+    image.set_src(url);
+    track(
+        name,
+        Source::Image {
+            image: image.clone(),
+            url: url.to_string(),
+        },
+        now,
+    );
+    Some(image)
+}
+
+/// Starts loading the theme's font. The browser falls back along the
+/// theme's font list for as long as it is missing.
+pub fn font(theme: &Theme, now: f64) {
+    let font = theme.font(16.0);
+    if let Some(fonts) = fonts() {
+        let _ = fonts.load(&font);
+    }
+    track("font", Source::Font(font), now);
+}
+
+/// Starts loading the web font at `url` through the FontFace API and adds
+/// it to the page as `family`. Text set in it shows in the next font of
+/// its list until it loads, and for good if it doesn't.
+pub fn face(family: &str, url: &str, now: f64) {
+    let source = Source::Face {
+        family: family.to_string(),
+        url: url.to_string(),
+        face: load_face(family, url),
+    };
+    track(family, source, now);
+}
+
+fn load_face(family: &str, url: &str) -> Option<FontFace> {
+    let face = FontFace::new_with_str(family, &format!("url({url})")).ok()?;
+    fonts()?.add(&face).ok()?;
+    let _ = face.load();
+    Some(face)
+}
+
+fn track(name: &str, source: Source, now: f64) {
+    ASSETS.with(|a| {
+        let mut a = a.borrow_mut();
+        a.list.retain(|asset| asset.name != name);
+        a.list.push(Asset {
+            name: name.to_string(),
+            source,
+            status: Status::Loading,
+            tries: 1,
+            started: now,
+        });
+        a.announced = false;
+    });
+}
+
+/// Checks on everything still loading. Returns the readiness report the
+/// first time nothing is left loading.
+pub fn poll(now: f64) -> Option<Ready> {
+    ASSETS.with(|a| {
+        let mut a = a.borrow_mut();
+        for asset in &mut a.list {
+            asset.poll(now);
+        }
+        if a.announced || a.list.iter().any(|asset| asset.status == Status::Loading) {
+            return None;
+        }
+        a.announced = true;
+        let failed = a.list.iter().filter(|asset| asset.status == Status::Failed);
+        Some(Ready {
+            failed: failed.map(|asset| asset.name.clone()).collect(),
+        })
+    })
+}
+
+/// How far along loading is, from 0 to 1, or `None` once it has settled.
+pub fn progress() -> Option<f64> {
+    ASSETS.with(|a| {
+        let a = a.borrow();
+        let loading = a
+            .list
+            .iter()
+            .filter(|asset| asset.status == Status::Loading);
+        let settled = a.list.len() - loading.count();
+        (settled < a.list.len()).then(|| settled as f64 / a.list.len() as f64)
+    })
+}
+
+/// Draws the loading screen: a bar filling up as assets come in.
+pub fn draw(
+    ctx: &CanvasRenderingContext2d,
+    (w, h): (f64, f64),
+    progress: f64,
+    theme: &Theme,
+) -> Result<(), JsValue> {
+    let (bar_w, bar_h) = (w * 0.6, 12.0);
+    let (x, y) = ((w - bar_w) / 2.0, h / 2.0);
+    ctx.set_fill_style_str(&theme.ink);
+    ctx.set_font(&theme.font(16.0));
+    ctx.set_text_align("center");
+    ctx.fill_text("Loading\u{2026}", w / 2.0, y - 12.0)?;
+    ctx.set_stroke_style_str(&theme.ink);
+    ctx.stroke_rect(x, y, bar_w, bar_h);
+    ctx.fill_rect(x, y, bar_w * progress, bar_h);
+    Ok(())
+}
+
+/// Whether every asset has loaded or been given up on, for pages that
+/// start listening with `on_ready` late.
+#[wasm_bindgen]
+pub fn assets_ready() -> bool {
+    progress().is_none()
+}
+
+fn fonts() -> Option<web_sys::FontFaceSet> {
+    Some(web_sys::window()?.document()?.fonts())
+}
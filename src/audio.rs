@@ -0,0 +1,195 @@
+use std::cell::RefCell;
+
+use wasm_bindgen::prelude::*;
+use web_sys::{
+    AudioContext, BiquadFilterNode, BiquadFilterType, GainNode, OscillatorNode, OscillatorType,
+};
+
+use crate::settings;
+
+// how far ahead of the audio clock notes are queued, in seconds; longer
+// than the slowest game tick, so the music never starves between steps
+const LOOKAHEAD: f64 = 0.5;
+// a sixteenth note at normal speed
+const BASE_STEP: f64 = 0.15;
+// the loop's bass line, in semitones above A2
+const PATTERN: [i32; 8] = [0, 12, 7, 12, 3, 15, 7, 10];
+const ROOT_HZ: f64 = 110.0;
+// how quickly the music fades in and out, as a time constant in seconds
+const FADE: f64 = 0.15;
+const MUSIC_LEVEL: f64 = 0.25;
+
+thread_local! {
+    static AUDIO: RefCell<Option<Audio>> = const { RefCell::new(None) };
+}
+
+/// A sound effect.
+#[derive(Clone, Copy)]
+pub enum Sound {
+    Eat,
+    Crash,
+}
+
+/// The page's sound, built on the first key press since browsers only let
+/// audio start from a user gesture. Music and effects each have a gain
+/// node set from their volume, and both feed the master gain.
+struct Audio {
+    ctx: AudioContext,
+    master: GainNode,
+    music_channel: GainNode,
+    sfx_channel: GainNode,
+    // the music's own level, faded out while nothing is being played
+    music: GainNode,
+    filter: BiquadFilterNode,
+    // audio-clock time of the next note, and which note of the loop it is
+    next_note: f64,
+    step: usize,
+    playing: bool,
+}
+
+impl Audio {
+    fn new() -> Result<Self, JsValue> {
+        let ctx = AudioContext::new()?;
+        let master = ctx.create_gain()?;
+        master.connect_with_audio_node(&ctx.destination())?;
+        let music_channel = ctx.create_gain()?;
+        music_channel.connect_with_audio_node(&master)?;
+        let sfx_channel = ctx.create_gain()?;
+        sfx_channel.connect_with_audio_node(&master)?;
+        let filter = ctx.create_biquad_filter()?;
+        filter.set_type(BiquadFilterType::Lowpass);
+        filter.connect_with_audio_node(&music_channel)?;
+        let music = ctx.create_gain()?;
+        music.gain().set_value(0.0);
+        music.connect_with_audio_node(&filter)?;
+        Ok(Self {
+            ctx,
+            master,
+            music_channel,
+            sfx_channel,
+            music,
+            filter,
+            next_note: 0.0,
+            step: 0,
+            playing: false,
+        })
+    }
+
+    fn note(&self, at: f64, length: f64) -> Result<(), JsValue> {
+        let semitones = PATTERN[self.step % PATTERN.len()];
+        let osc: OscillatorNode = self.ctx.create_oscillator()?;
+        osc.set_type(OscillatorType::Triangle);
+        osc.frequency()
+            .set_value((ROOT_HZ * 2f64.powf(f64::from(semitones) / 12.0)) as f32);
+        let env = self.ctx.create_gain()?;
+        env.gain().set_value_at_time(0.0, at)?;
+        env.gain().linear_ramp_to_value_at_time(1.0, at + 0.01)?;
+        env.gain()
+            .set_target_at_time(0.0, at + 0.02, length / 3.0)?;
+        osc.connect_with_audio_node(&env)?;
+        env.connect_with_audio_node(&self.music)?;
+        osc.start_with_when(at)?;
+        osc.stop_with_when(at + length)?;
+        Ok(())
+    }
+
+    fn update(&mut self, intensity: Option<f64>) -> Result<(), JsValue> {
+        let now = self.ctx.current_time();
+        let (master, music, sfx) = settings::with(|s| (s.volume, s.music_volume, s.sfx_volume));
+        for (node, level) in [
+            (&self.master, master),
+            (&self.music_channel, music),
+            (&self.sfx_channel, sfx),
+        ] {
+            node.gain().set_target_at_time(level as f32, now, FADE)?;
+        }
+        let Some(intensity) = intensity.filter(|_| settings::with(|s| s.music)) else {
+            if self.playing {
+                self.playing = false;
+                self.music.gain().set_target_at_time(0.0, now, FADE)?;
+            }
+            return Ok(());
+        };
+        if !self.playing {
+            // pick up from the next note rather than catching up on the
+            // ones skipped while silent
+            self.playing = true;
+            self.next_note = now;
+            self.music
+                .gain()
+                .set_target_at_time(MUSIC_LEVEL as f32, now, FADE)?;
+        }
+        self.filter
+            .frequency()
+            .set_target_at_time((600.0 * intensity) as f32, now, 0.5)?;
+        let step = BASE_STEP / intensity;
+        // a throttled background tab can leave the queue behind the clock
+        self.next_note = self.next_note.max(now);
+        while self.next_note < now + LOOKAHEAD {
+            self.note(self.next_note, step)?;
+            self.next_note += step;
+            self.step += 1;
+        }
+        Ok(())
+    }
+
+    // A short pitch sweep from `from` to `to` Hz.
+    fn sweep(&self, wave: OscillatorType, from: f64, to: f64, length: f64) -> Result<(), JsValue> {
+        let at = self.ctx.current_time();
+        let osc = self.ctx.create_oscillator()?;
+        osc.set_type(wave);
+        osc.frequency().set_value_at_time(from as f32, at)?;
+        osc.frequency()
+            .exponential_ramp_to_value_at_time(to as f32, at + length)?;
+        let env = self.ctx.create_gain()?;
+        env.gain().set_value_at_time(0.3, at)?;
+        env.gain().linear_ramp_to_value_at_time(0.0, at + length)?;
+        osc.connect_with_audio_node(&env)?;
+        env.connect_with_audio_node(&self.sfx_channel)?;
+        osc.start_with_when(at)?;
+        osc.stop_with_when(at + length)?;
+        Ok(())
+    }
+}
+
+/// Plays a sound effect, if the audio has started.
+pub fn play(sound: Sound) {
+    AUDIO.with(|audio| {
+        if let Some(a) = audio.borrow().as_ref() {
+            let _ = match sound {
+                Sound::Eat => a.sweep(OscillatorType::Square, 660.0, 990.0, 0.08),
+                Sound::Crash => a.sweep(OscillatorType::Sawtooth, 220.0, 55.0, 0.4),
+            };
+        }
+    });
+}
+
+/// Starts the audio context, from inside a key or click handler.
+pub fn unlock() {
+    AUDIO.with(|audio| {
+        let mut audio = audio.borrow_mut();
+        if audio.is_none() {
+            *audio = Audio::new().ok();
+        }
+        if let Some(a) = audio.as_ref() {
+            let _ = a.ctx.resume();
+        }
+    });
+}
+
+/// Keeps the music going at `intensity` (1 at the normal speed, more as
+/// the game speeds up), or fades it out for `None`. Called on every step
+/// of the game loop, whatever the game is doing.
+pub fn music(intensity: Option<f64>) {
+    AUDIO.with(|audio| {
+        if let Some(a) = audio.borrow_mut().as_mut() {
+            let _ = a.update(intensity);
+        }
+    });
+}
+
+/// Turns the background music on or off.
+#[wasm_bindgen]
+pub fn set_music(on: bool) {
+    settings::update(|s| s.music = on);
+}
@@ -0,0 +1,143 @@
+use std::cell::{Cell, RefCell};
+
+use serde::{Deserialize, Serialize};
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, Document, HtmlCanvasElement};
+
+use crate::core::rng::Rng;
+use crate::quality;
+use crate::settings;
+use crate::theme::Theme;
+
+// the layers drift slowly, so a few redraws a second look smooth enough
+const REDRAW_MS: f64 = 100.0;
+const SPECKS: usize = 90;
+const CLOUD_SPECKS: usize = 8;
+// how far the nearest layer drifts each second, in pixels
+const DRIFT: f64 = 12.0;
+
+/// What drifts behind the board in a theme.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scenery {
+    Starfield,
+    Clouds,
+}
+
+/// Animated scenery drawn beneath the board. It renders into a canvas of
+/// its own a few times a second and every frame in between copies that,
+/// so the scenery costs little more than the plain background did.
+pub struct Backdrop {
+    canvas: HtmlCanvasElement,
+    ctx: CanvasRenderingContext2d,
+    // (x, y) as fractions of the board, and depth from 1 (far) to 3 (near)
+    specks: Vec<(f64, f64, u32)>,
+    // the board's size in CSS pixels, and device pixels to each
+    size: Cell<(f64, f64)>,
+    scale: Cell<f64>,
+    drawn_at: Cell<f64>,
+    drawn_theme: RefCell<Option<Theme>>,
+}
+
+impl Backdrop {
+    pub fn new(document: &Document) -> Result<Self, JsValue> {
+        let canvas: HtmlCanvasElement = document.create_element("canvas")?.dyn_into()?;
+        let ctx = canvas
+            .get_context("2d")?
+            .ok_or("no 2d context")?
+            .dyn_into::<CanvasRenderingContext2d>()?;
+        // fixed, so the sky is the same on every load
+        let mut rng = Rng::new(0x5eed);
+        let mut frac = || f64::from(rng.below(1000)) / 1000.0;
+        let specks = (0..SPECKS)
+            .map(|_| (frac(), frac(), 1 + (frac() * 3.0) as u32))
+            .collect();
+        Ok(Self {
+            canvas,
+            ctx,
+            specks,
+            size: Cell::new((0.0, 0.0)),
+            scale: Cell::new(1.0),
+            drawn_at: Cell::new(f64::NEG_INFINITY),
+            drawn_theme: RefCell::new(None),
+        })
+    }
+
+    /// Sizes the scenery to a board `width` by `height` CSS pixels, with
+    /// `scale` device pixels to each.
+    pub fn resize(&self, (width, height): (f64, f64), scale: f64) {
+        self.canvas.set_width((width * scale).round() as u32);
+        self.canvas.set_height((height * scale).round() as u32);
+        self.size.set((width, height));
+        self.scale.set(scale);
+        self.drawn_at.set(f64::NEG_INFINITY);
+    }
+
+    /// Paints the scenery for `theme` onto `target`. Returns false without
+    /// drawing when animation is off, for battery saver, reduced motion or
+    /// the player's setting, so the caller fills a plain background.
+    pub fn draw(
+        &self,
+        target: &CanvasRenderingContext2d,
+        theme: &Theme,
+        now: f64,
+    ) -> Result<bool, JsValue> {
+        if !settings::with(|s| s.backdrop) || quality::low_power() || quality::reduced_motion() {
+            return Ok(false);
+        }
+        if now - self.drawn_at.get() >= REDRAW_MS
+            || self.drawn_theme.borrow().as_ref() != Some(theme)
+        {
+            self.render(theme, now)?;
+            self.drawn_at.set(now);
+            self.drawn_theme.replace(Some(theme.clone()));
+        }
+        let (w, h) = self.size.get();
+        target.draw_image_with_html_canvas_element_and_dw_and_dh(&self.canvas, 0.0, 0.0, w, h)?;
+        Ok(true)
+    }
+
+    fn render(&self, theme: &Theme, now: f64) -> Result<(), JsValue> {
+        let (w, h) = self.size.get();
+        let scale = self.scale.get();
+        self.ctx.set_transform(scale, 0.0, 0.0, scale, 0.0, 0.0)?;
+        self.ctx.set_fill_style_str(&theme.background);
+        self.ctx.fill_rect(0.0, 0.0, w, h);
+        let secs = now / 1000.0;
+        // nearer layers drift faster, which gives the parallax
+        let x =
+            |fx: f64, depth: u32| (fx * w - secs * DRIFT * f64::from(depth) / 3.0).rem_euclid(w);
+        match theme.scenery {
+            Scenery::Starfield => {
+                for &(fx, fy, depth) in &self.specks {
+                    let size = f64::from(depth);
+                    let alpha = 0.25 * f64::from(depth);
+                    self.ctx
+                        .set_fill_style_str(&format!("rgba(255, 255, 255, {alpha})"));
+                    self.ctx.fill_rect(x(fx, depth), fy * h, size, size);
+                }
+            }
+            Scenery::Clouds => {
+                for &(fx, fy, depth) in &self.specks[..CLOUD_SPECKS] {
+                    let r = w / 20.0 * f64::from(depth);
+                    let (cx, cy) = (x(fx, depth), fy * h);
+                    self.ctx.set_fill_style_str("rgba(150, 180, 210, 0.15)");
+                    // a few overlapping puffs, drawn again a board's width
+                    // to the left so clouds slide in from the edge
+                    for dx in [0.0, -w] {
+                        self.ctx.begin_path();
+                        for (px, py, pr) in [(-r, 0.0, 0.7), (0.0, -r * 0.3, 1.0), (r, 0.0, 0.7)] {
+                            let (px, py) = (cx + dx + px, cy + py);
+                            self.ctx.move_to(px + r * pr, py);
+                            self.ctx.arc(px, py, r * pr, 0.0, std::f64::consts::TAU)?;
+                        }
+                        self.ctx.fill();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,227 @@
+//! Plays thousands of games with no browser and reports how they went, for
+//! benchmarking the rules and balancing them:
+//!
+//! ```text
+//! cargo run --release --bin sim -- --games 5000 --policy cautious --rules walls
+//! ```
+//!
+//! `--policy` is a bot personality, `random`, or `script:` followed by
+//! `U`, `D`, `L`, `R` or `.` per tick, repeated for as long as a game lasts.
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    if let Err(e) = native::run(std::env::args().skip(1)) {
+        eprintln!("sim: {e}");
+        std::process::exit(2);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::time::{Duration, Instant};
+
+    use rayon::prelude::*;
+
+    use snake_game::core::batch::BatchSim;
+    use snake_game::core::board::{Board, Rules};
+    use snake_game::core::bot::{self, Personality};
+    use snake_game::core::rng::Rng;
+
+    const DIRS: [(i32, i32); 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+    // width of the score histogram's buckets
+    const BUCKET: i32 = 5;
+
+    struct Options {
+        games: usize,
+        seed: u64,
+        rules: Rules,
+        policy: Policy,
+        max_ticks: u32,
+    }
+
+    enum Policy {
+        Bot(Personality),
+        Random,
+        Script(Vec<Option<(i32, i32)>>),
+    }
+
+    impl Policy {
+        fn parse(text: &str) -> Result<Self, String> {
+            if text == "random" {
+                return Ok(Policy::Random);
+            }
+            if let Some(script) = text.strip_prefix("script:") {
+                let steps = script
+                    .chars()
+                    .map(|c| match c {
+                        'U' => Ok(Some((0, -1))),
+                        'D' => Ok(Some((0, 1))),
+                        'L' => Ok(Some((-1, 0))),
+                        'R' => Ok(Some((1, 0))),
+                        '.' => Ok(None),
+                        _ => Err(format!("unknown script step {c:?}")),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if steps.is_empty() {
+                    return Err("the script is empty".to_string());
+                }
+                return Ok(Policy::Script(steps));
+            }
+            Personality::parse(text)
+                .map(Policy::Bot)
+                .ok_or_else(|| format!("unknown policy {text:?}"))
+        }
+
+        // The heading for a game at `board`, or `None` to keep its own.
+        fn steer(&self, board: &Board, rng: &mut Rng) -> Option<(i32, i32)> {
+            match self {
+                Policy::Bot(personality) => {
+                    let food = board.nearest_food(0)?;
+                    Some(bot::steer(board, 0, food, *personality))
+                }
+                Policy::Random => Some(DIRS[rng.below(DIRS.len() as i32) as usize]),
+                Policy::Script(steps) => steps[board.tick as usize % steps.len()],
+            }
+        }
+    }
+
+    fn options(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options {
+            games: 1000,
+            seed: 1,
+            rules: Rules::default(),
+            policy: Policy::Bot(Personality::Greedy),
+            max_ticks: 10_000,
+        };
+        while let Some(flag) = args.next() {
+            let value = args.next().ok_or_else(|| format!("{flag} needs a value"))?;
+            let number = || format!("{flag} needs a number");
+            match flag.as_str() {
+                "--games" => options.games = value.parse().map_err(|_| number())?,
+                "--seed" => options.seed = value.parse().map_err(|_| number())?,
+                "--max-ticks" => options.max_ticks = value.parse().map_err(|_| number())?,
+                "--rules" => {
+                    options.rules = Rules::decode(&value).ok_or("--rules is like walls+tiny")?
+                }
+                "--policy" => options.policy = Policy::parse(&value)?,
+                _ => return Err(format!("unknown option {flag}")),
+            }
+        }
+        if options.games == 0 {
+            return Err("--games must be at least 1".to_string());
+        }
+        Ok(options)
+    }
+
+    // How one game ended.
+    struct Finish {
+        score: i32,
+        length: usize,
+        ticks: u32,
+    }
+
+    pub fn run(args: impl Iterator<Item = String>) -> Result<(), String> {
+        let options = options(args)?;
+        let mut sim = BatchSim::new(options.games, options.seed, options.rules);
+        let mut rngs: Vec<Rng> = (0..options.games as u64)
+            .map(|i| Rng::new(options.seed ^ i.rotate_left(32)))
+            .collect();
+        let mut finishes: Vec<Option<Finish>> = (0..options.games).map(|_| None).collect();
+        let (mut thinking, mut stepping) = (Duration::ZERO, Duration::ZERO);
+        let mut game_ticks = 0u64;
+        let started = Instant::now();
+        for tick in 1..=options.max_ticks {
+            let clock = Instant::now();
+            let dirs: Vec<(i32, i32)> = rngs
+                .par_iter_mut()
+                .enumerate()
+                .map(|(i, rng)| {
+                    let board = sim.board(i);
+                    let policy = options.policy.steer(board, rng);
+                    policy.unwrap_or(board.snakes[0].dir)
+                })
+                .collect();
+            thinking += clock.elapsed();
+            let clock = Instant::now();
+            let outcomes = sim.step(&dirs);
+            stepping += clock.elapsed();
+            for (i, outcome) in outcomes.iter().enumerate() {
+                if finishes[i].is_some() {
+                    continue;
+                }
+                game_ticks += 1;
+                if outcome.over || tick == options.max_ticks {
+                    let board = sim.board(i);
+                    finishes[i] = Some(Finish {
+                        score: outcome.score,
+                        length: board.snakes[0].body.len(),
+                        ticks: board.tick,
+                    });
+                }
+            }
+            if finishes.iter().all(Option::is_some) {
+                break;
+            }
+        }
+        let finishes: Vec<Finish> = finishes.into_iter().flatten().collect();
+        report(&options, &finishes, game_ticks, (thinking, stepping));
+        println!("wall time: {:.2?}", started.elapsed());
+        Ok(())
+    }
+
+    fn report(
+        options: &Options,
+        finishes: &[Finish],
+        game_ticks: u64,
+        (thinking, stepping): (Duration, Duration),
+    ) {
+        let count = finishes.len() as f64;
+        let mut scores: Vec<i32> = finishes.iter().map(|f| f.score).collect();
+        scores.sort_unstable();
+        let at = |q: f64| scores[((scores.len() - 1) as f64 * q).round() as usize];
+        let mean = |total: f64| total / count;
+        let lengths: usize = finishes.iter().map(|f| f.length).sum();
+        let ticks: u64 = finishes.iter().map(|f| u64::from(f.ticks)).sum();
+        let survived = finishes
+            .iter()
+            .filter(|f| f.ticks >= options.max_ticks)
+            .count();
+        println!(
+            "{} games, rules {}, seed {}",
+            finishes.len(),
+            options.rules.encode(),
+            options.seed
+        );
+        println!(
+            "score: mean {:.2}, min {}, median {}, p90 {}, max {}",
+            mean(scores.iter().map(|&s| f64::from(s)).sum()),
+            at(0.0),
+            at(0.5),
+            at(0.9),
+            at(1.0)
+        );
+        println!(
+            "length: mean {:.2}; ticks: mean {:.1}; {survived} still alive at {} ticks",
+            mean(lengths as f64),
+            mean(ticks as f64),
+            options.max_ticks
+        );
+        let per_tick = |d: Duration| d.as_nanos() as f64 / game_ticks.max(1) as f64;
+        println!(
+            "per game tick: {:.0} ns simulating, {:.0} ns choosing moves",
+            per_tick(stepping),
+            per_tick(thinking)
+        );
+        println!("scores:");
+        let top = at(1.0) / BUCKET;
+        for bucket in 0..=top {
+            let low = bucket * BUCKET;
+            let n = scores.iter().filter(|&&s| s / BUCKET == bucket).count();
+            let bar = "#".repeat((n * 50).div_ceil(scores.len()));
+            println!("  {:>4}-{:<4} {:>6} {bar}", low, low + BUCKET - 1, n);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+// a full meter lasts this long boosting
+const DRAIN_MS: f64 = 3000.0;
+// and refills from empty in this long
+const RECHARGE_MS: f64 = 6000.0;
+// once emptied, the meter has to refill this far before boosting again
+const RECOVER: f64 = 0.25;
+/// Ticks per tick of the game clock while boosting.
+pub const FACTOR: f64 = 2.0;
+
+/// Hold-to-boost for the local player: while Shift is held the game runs
+/// at double speed, draining a stamina meter that refills when it isn't.
+/// It only changes how often the board ticks, never the board itself, so
+/// replays play back the same however it was used.
+pub struct Boost {
+    held: bool,
+    stamina: f64,
+    // emptied, and not yet refilled to `RECOVER`
+    winded: bool,
+    active: bool,
+}
+
+impl Default for Boost {
+    fn default() -> Self {
+        Self {
+            held: false,
+            stamina: 1.0,
+            winded: false,
+            active: false,
+        }
+    }
+}
+
+impl Boost {
+    pub fn hold(&mut self, on: bool) {
+        self.held = on;
+    }
+
+    /// Runs the meter on by `ms`. `allowed` is whether the game would take
+    /// a boost at all right now.
+    pub fn step(&mut self, ms: f64, allowed: bool) {
+        self.active = allowed && self.held && !self.winded && self.stamina > 0.0;
+        if self.active {
+            self.stamina = (self.stamina - ms / DRAIN_MS).max(0.0);
+            self.winded = self.stamina == 0.0;
+        } else {
+            self.stamina = (self.stamina + ms / RECHARGE_MS).min(1.0);
+            self.winded &= self.stamina < RECOVER;
+        }
+    }
+
+    /// Whether the last step was boosted.
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    /// How full the meter is, from 0 to 1.
+    pub fn stamina(&self) -> f64 {
+        self.stamina
+    }
+
+    /// A full meter, for a new run.
+    pub fn reset(&mut self) {
+        *self = Self {
+            held: self.held,
+            ..Self::default()
+        };
+    }
+}
@@ -0,0 +1,79 @@
+use std::cell::RefCell;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::assets::Ready;
+
+/// Something that happened in a game, for the parts of the page that react
+/// to it. The game emits these as it runs and hands them out once per
+/// tick, so features listen here rather than being called from the loop.
+/// Levels come every `core::perk::LEVEL_POINTS` points.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    FoodEaten {
+        snake: usize,
+        score: i32,
+    },
+    Died {
+        snake: usize,
+    },
+    DirectionChanged {
+        snake: usize,
+        dir: (i32, i32),
+    },
+    LevelUp {
+        snake: usize,
+        level: i32,
+    },
+    ModeToggled(Mode),
+    Ticked {
+        tick: u32,
+    },
+    GameOver,
+    /// Every asset has loaded or been given up on.
+    AssetsReady(Ready),
+}
+
+/// What the board is being used for.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    Solo,
+    Versus,
+    Tournament,
+    Correspondence,
+    Roguelike,
+    Royale,
+    Replay,
+    Demo,
+}
+
+impl Mode {
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Solo => "solo",
+            Mode::Versus => "versus",
+            Mode::Tournament => "tournament",
+            Mode::Correspondence => "correspondence",
+            Mode::Roguelike => "roguelike",
+            Mode::Royale => "royale",
+            Mode::Replay => "replay",
+            Mode::Demo => "demo",
+        }
+    }
+}
+
+thread_local! {
+    static QUEUE: RefCell<Vec<GameEvent>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn emit(event: GameEvent) {
+    QUEUE.with(|q| q.borrow_mut().push(event));
+}
+
+/// Takes every event emitted since the last call, oldest first.
+pub fn drain() -> Vec<GameEvent> {
+    QUEUE.take()
+}
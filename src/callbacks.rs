@@ -0,0 +1,127 @@
+use std::cell::RefCell;
+
+use js_sys::Function;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::assets::Ready;
+use crate::bus::Mode;
+
+#[derive(Default)]
+struct Listeners {
+    game_over: Option<Function>,
+    score: Option<Function>,
+    tick: Option<Function>,
+    summary: Option<Function>,
+    ready: Option<Function>,
+}
+
+thread_local! {
+    static LISTENERS: RefCell<Listeners> = RefCell::new(Listeners::default());
+}
+
+#[derive(Serialize)]
+pub struct GameOver {
+    pub mode: Mode,
+    /// This browser's score.
+    pub score: i32,
+    /// Every snake's score, in snake order.
+    pub scores: Vec<i32>,
+    pub ticks: u32,
+}
+
+#[derive(Serialize)]
+pub struct Score {
+    pub snake: usize,
+    /// Whether `snake` is steered from this browser.
+    pub local: bool,
+    pub score: i32,
+}
+
+#[derive(Serialize)]
+pub struct Tick {
+    pub mode: Mode,
+    pub tick: u32,
+    pub scores: Vec<i32>,
+}
+
+/// Calls `callback` with a JSON `{mode, score, scores, ticks}` whenever a
+/// game ends; `undefined` stops.
+#[wasm_bindgen]
+pub fn on_game_over(callback: Option<Function>) {
+    LISTENERS.with(|l| l.borrow_mut().game_over = callback);
+}
+
+#[derive(Serialize)]
+pub struct Summary {
+    pub text: String,
+}
+
+/// Calls `callback` with a JSON `{snake, local, score}` whenever a snake
+/// eats; `undefined` stops.
+#[wasm_bindgen]
+pub fn on_score(callback: Option<Function>) {
+    LISTENERS.with(|l| l.borrow_mut().score = callback);
+}
+
+/// Calls `callback` with a JSON `{mode, tick, scores}` after every game
+/// tick; `undefined` stops.
+#[wasm_bindgen]
+pub fn on_tick(callback: Option<Function>) {
+    LISTENERS.with(|l| l.borrow_mut().tick = callback);
+}
+
+/// Hands `copy_session_summary` to `callback` as a JSON `{text}` instead
+/// of copying it; `undefined` goes back to the clipboard.
+#[wasm_bindgen]
+pub fn on_summary(callback: Option<Function>) {
+    LISTENERS.with(|l| l.borrow_mut().summary = callback);
+}
+
+/// Calls `callback` with a JSON `{failed}` once the sprite sheet and fonts
+/// have loaded, listing any the game is doing without; `undefined` stops.
+/// It is called again after a new sprite sheet or theme loads.
+#[wasm_bindgen]
+pub fn on_ready(callback: Option<Function>) {
+    LISTENERS.with(|l| l.borrow_mut().ready = callback);
+}
+
+/// A callback waiting to be made. The game collects these while it is
+/// borrowed and they are made once it is let go, since a page's handler is
+/// free to call back into the game.
+pub enum Call {
+    GameOver(GameOver),
+    Score(Score),
+    Tick(Tick),
+    Ready(Ready),
+}
+
+/// Makes `calls` in order.
+pub fn fire(calls: Vec<Call>) {
+    for c in calls {
+        match c {
+            Call::GameOver(payload) => call(|l| &l.game_over, &payload),
+            Call::Score(payload) => call(|l| &l.score, &payload),
+            Call::Tick(payload) => call(|l| &l.tick, &payload),
+            Call::Ready(payload) => call(|l| &l.ready, &payload),
+        }
+    }
+}
+
+/// Whether a page took the summary.
+pub fn summary(payload: &Summary) -> bool {
+    let taken = LISTENERS.with(|l| l.borrow().summary.is_some());
+    call(|l| &l.summary, payload);
+    taken
+}
+
+// The callback is cloned out first so it can set listeners itself.
+fn call(which: impl Fn(&Listeners) -> &Option<Function>, payload: &impl Serialize) {
+    let Some(callback) = LISTENERS.with(|l| which(&l.borrow()).clone()) else {
+        return;
+    };
+    let Ok(json) = serde_json::to_string(payload) else {
+        return;
+    };
+    let _ = callback.call1(&JsValue::NULL, &JsValue::from_str(&json));
+}
@@ -0,0 +1,77 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use wasm_bindgen::prelude::*;
+use web_sys::CanvasRenderingContext2d;
+
+use crate::core::rng::Rng;
+use crate::quality;
+
+// how long a shake takes to die down, as a time constant in ms
+const DECAY_MS: f64 = 120.0;
+// past this a shake is over
+const SHAKE_MS: f64 = 500.0;
+// the shake picks a new direction this often, in ms
+const JOLT_MS: f64 = 16.0;
+
+/// How the board is placed on the canvas: turned for the rotating board
+/// and knocked about by screen shake. Everything drawn on the board goes
+/// through it, so a shake moves it all together.
+#[derive(Default)]
+pub struct Camera {
+    // when the shake began, and how hard, in cells
+    shake: Option<(f64, f64)>,
+}
+
+impl Camera {
+    /// Shakes the board by up to `strength` cells, dying away over half a
+    /// second. A weaker shake doesn't cut a stronger one short.
+    pub fn shake(&mut self, strength: f64, now: f64) {
+        if quality::reduced_motion() {
+            return;
+        }
+        if self.offset_strength(now) < strength {
+            self.shake = Some((now, strength));
+        }
+    }
+
+    // How far the shake can throw the board at `now`, in cells.
+    fn offset_strength(&self, now: f64) -> f64 {
+        match self.shake {
+            Some((start, strength)) if now - start < SHAKE_MS => {
+                strength * (-(now - start) / DECAY_MS).exp()
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Where the shake has thrown the board at `now`, in cells.
+    pub fn offset(&self, now: f64) -> (f64, f64) {
+        let strength = self.offset_strength(now);
+        if strength <= 0.0 {
+            return (0.0, 0.0);
+        }
+        let mut rng = Rng::new((now / JOLT_MS) as u64);
+        let angle = 2.0 * PI * f64::from(rng.below(360)) / 360.0;
+        (angle.cos() * strength, angle.sin() * strength)
+    }
+
+    /// Sets `ctx` up to draw a board `w` by `h` pixels of `cell`-pixel
+    /// cells, turned `turns` quarter turns about its centre, at `now`.
+    pub fn apply(
+        &self,
+        ctx: &CanvasRenderingContext2d,
+        (w, h): (f64, f64),
+        cell: f64,
+        turns: u32,
+        now: f64,
+    ) -> Result<(), JsValue> {
+        let (dx, dy) = self.offset(now);
+        ctx.translate(dx * cell, dy * cell)?;
+        if turns > 0 {
+            ctx.translate(w / 2.0, h / 2.0)?;
+            ctx.rotate(f64::from(turns) * FRAC_PI_2)?;
+            ctx.translate(-w / 2.0, -h / 2.0)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,950 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::core::entity::{Entity, Kind};
+use crate::core::handicap::Handicap;
+use crate::core::perk::{Perk, Perks, LEVEL_POINTS};
+use crate::core::rng::Rng;
+use crate::core::{HEIGHT, WIDTH};
+
+/// Everything that can change a board. The game, replays, save files and
+/// versus play all describe a run as a list of these, and `Board::apply` is
+/// the only place they take effect. `ScoreAwarded` carries points from
+/// plugins on top of what the board scores itself, `PerkGained` a
+/// roguelike pick and `AutopilotSet` marks where a bot took over steering
+/// (or gave it back).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Event {
+    TurnRequested { snake: usize, dir: (i32, i32) },
+    TickAdvanced,
+    FoodSpawned { pos: (i32, i32) },
+    ScoreAwarded { snake: usize, points: i32 },
+    PerkGained { snake: usize, perk: Perk },
+    AutopilotSet { snake: usize, on: bool },
+}
+
+/// Whether `dir` is one of the four headings a snake can take, a single
+/// step up, down, left or right. Inputs from outside the game are checked
+/// against this, since the board turns to whatever it is given.
+pub fn is_heading(dir: (i32, i32)) -> bool {
+    dir.0.abs() + dir.1.abs() == 1
+}
+
+// ticks between quarter turns of a rotating board
+const ROTATE_TICKS: u32 = 50;
+/// The most food a board puts out at once.
+pub const MAX_FOOD: u32 = 5;
+/// The most of the arena obstacles can cover, in percent.
+pub const MAX_OBSTACLES: u32 = 20;
+// where the obstacles' scatter comes from, the same for every board
+const OBSTACLE_SEED: u64 = 0x0b57_ac1e;
+
+/// Options that change how a board plays. They are fixed for a whole run
+/// and travel with replays and versus seeds so every copy plays alike.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Rules {
+    pub wrap: WrapConfig,
+    #[serde(default)]
+    pub mutators: Mutators,
+    #[serde(default)]
+    pub shrink: Shrink,
+    /// How many pieces of food are out at once, from 1 to `MAX_FOOD`.
+    #[serde(default = "one")]
+    pub food_count: u32,
+    /// How much of the arena is blocked by obstacles, in percent up to
+    /// `MAX_OBSTACLES`.
+    #[serde(default)]
+    pub obstacles: u32,
+}
+
+fn one() -> u32 {
+    1
+}
+
+/// Which edges of the arena snakes wrap across. The others are walls.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "WrapRepr")]
+pub struct WrapConfig {
+    /// The left and right edges.
+    pub x: bool,
+    /// The top and bottom edges.
+    pub y: bool,
+}
+
+impl WrapConfig {
+    /// Wrapping across every edge, or none.
+    pub const fn both(on: bool) -> Self {
+        Self { x: on, y: on }
+    }
+}
+
+// Before wrapping was per axis it was one flag for both, which replays,
+// saves and settings written then still hold.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WrapRepr {
+    Both(bool),
+    Axes { x: bool, y: bool },
+}
+
+impl From<WrapRepr> for WrapConfig {
+    fn from(repr: WrapRepr) -> Self {
+        match repr {
+            WrapRepr::Both(on) => Self::both(on),
+            WrapRepr::Axes { x, y } => Self { x, y },
+        }
+    }
+}
+
+/// Twists picked before a run. Each one only changes its own part of the
+/// rules, so any mix of them can be on at once.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Mutators {
+    /// The four cells diagonal to the food are deadly.
+    pub poison: bool,
+    /// The board turns a quarter every `ROTATE_TICKS` ticks, controls
+    /// included.
+    pub rotate: bool,
+    /// Two ticks per tick of the game clock.
+    pub double_speed: bool,
+    /// Play is confined to the middle of the board.
+    pub tiny: bool,
+    /// A pair of portals a quarter of the way in from opposite corners.
+    /// A head moving onto one comes out of the other, heading the same way.
+    pub portals: bool,
+}
+
+impl Mutators {
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "poison" => Some(&mut self.poison),
+            "rotate" => Some(&mut self.rotate),
+            "double_speed" => Some(&mut self.double_speed),
+            "tiny" => Some(&mut self.tiny),
+            "portals" => Some(&mut self.portals),
+            _ => None,
+        }
+    }
+
+    /// The names of the mutators that are on.
+    pub fn names(&self) -> Vec<&'static str> {
+        [
+            (self.poison, "poison"),
+            (self.rotate, "rotate"),
+            (self.double_speed, "double_speed"),
+            (self.tiny, "tiny"),
+            (self.portals, "portals"),
+        ]
+        .into_iter()
+        .filter_map(|(on, name)| on.then_some(name))
+        .collect()
+    }
+}
+
+/// An arena that closes in a ring at a time, for battle royales. A board
+/// with one plays on to the last snake standing rather than ending at the
+/// first crash, and the snakes that crash stay where they fell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Shrink {
+    /// Ticks between rings closing, or 0 for an arena that stays put.
+    pub every: u32,
+    /// The narrowest the arena closes to, in cells.
+    pub min_side: i32,
+}
+
+impl Shrink {
+    pub fn on(&self) -> bool {
+        self.every > 0
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            wrap: WrapConfig::both(true),
+            mutators: Mutators::default(),
+            shrink: Shrink::default(),
+            food_count: 1,
+            obstacles: 0,
+        }
+    }
+}
+
+impl Rules {
+    /// `wrap`, `walls`, `wrap_x` or `wrap_y`, followed by `+name` for each
+    /// mutator and `+shrink:every:min_side` for a shrinking arena.
+    pub fn encode(&self) -> String {
+        let mut text = String::from(match (self.wrap.x, self.wrap.y) {
+            (true, true) => "wrap",
+            (false, false) => "walls",
+            (true, false) => "wrap_x",
+            (false, true) => "wrap_y",
+        });
+        for name in self.mutators.names() {
+            text.push('+');
+            text.push_str(name);
+        }
+        if self.shrink.on() {
+            text.push_str(&format!(
+                "+shrink:{}:{}",
+                self.shrink.every, self.shrink.min_side
+            ));
+        }
+        if self.food_count != 1 {
+            text.push_str(&format!("+food:{}", self.food_count));
+        }
+        if self.obstacles > 0 {
+            text.push_str(&format!("+obstacles:{}", self.obstacles));
+        }
+        text
+    }
+
+    pub fn decode(text: &str) -> Option<Rules> {
+        let mut parts = text.split('+');
+        let wrap = match parts.next()? {
+            "wrap" => WrapConfig::both(true),
+            "walls" => WrapConfig::both(false),
+            "wrap_x" => WrapConfig { x: true, y: false },
+            "wrap_y" => WrapConfig { x: false, y: true },
+            _ => return None,
+        };
+        let mut rules = Rules {
+            wrap,
+            ..Rules::default()
+        };
+        for name in parts {
+            if let Some(schedule) = name.strip_prefix("shrink:") {
+                let (every, min_side) = schedule.split_once(':')?;
+                rules.shrink = Shrink {
+                    every: every.parse().ok()?,
+                    min_side: min_side.parse().ok()?,
+                };
+            } else if let Some(count) = name.strip_prefix("food:") {
+                rules.food_count = count.parse().ok()?;
+            } else if let Some(percent) = name.strip_prefix("obstacles:") {
+                rules.obstacles = percent.parse().ok()?;
+            } else {
+                *rules.mutators.get_mut(name)? = true;
+            }
+        }
+        rules.valid().then_some(rules)
+    }
+
+    /// Whether the food count and obstacles are within their limits.
+    pub fn valid(&self) -> bool {
+        (1..=MAX_FOOD).contains(&self.food_count) && self.obstacles <= MAX_OBSTACLES
+    }
+
+    /// The playable cells, from the first corner up to but not including
+    /// the second.
+    pub fn arena(&self) -> ((i32, i32), (i32, i32)) {
+        if self.mutators.tiny {
+            (
+                (WIDTH / 4, HEIGHT / 4),
+                (WIDTH * 3 / 4 + 1, HEIGHT * 3 / 4 + 1),
+            )
+        } else {
+            ((0, 0), (WIDTH, HEIGHT))
+        }
+    }
+
+    /// Rings closed in from the arena's edges by `tick`.
+    pub fn rings(&self, tick: u32) -> i32 {
+        if !self.shrink.on() {
+            return 0;
+        }
+        let ((x0, y0), (x1, y1)) = self.arena();
+        let most = ((x1 - x0).min(y1 - y0) - self.shrink.min_side.max(1)).max(0) / 2;
+        ((tick / self.shrink.every) as i32).min(most)
+    }
+
+    /// Whether `pos` has been closed off by `tick`, which is deadly.
+    pub fn closed(&self, tick: u32, (x, y): (i32, i32)) -> bool {
+        let ring = self.rings(tick);
+        let ((x0, y0), (x1, y1)) = self.arena();
+        x < x0 + ring || y < y0 + ring || x >= x1 - ring || y >= y1 - ring
+    }
+
+    /// The portals on the board, in linked pairs.
+    pub fn portals(&self) -> Vec<((i32, i32), (i32, i32))> {
+        if !self.mutators.portals {
+            return Vec::new();
+        }
+        let ((x0, y0), (x1, y1)) = self.arena();
+        let (dx, dy) = ((x1 - x0) / 4, (y1 - y0) / 4);
+        vec![((x0 + dx, y0 + dy), (x1 - 1 - dx, y1 - 1 - dy))]
+    }
+
+    /// The other end of the portal at `pos`, if there is one.
+    pub fn portal(&self, pos: (i32, i32)) -> Option<(i32, i32)> {
+        self.portals().into_iter().find_map(|(a, b)| {
+            if pos == a {
+                Some(b)
+            } else if pos == b {
+                Some(a)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Whether `pos` is an obstacle, which is a wall. They are scattered
+    /// the same way on every board, never on a portal or along the rows
+    /// snakes start on.
+    pub fn obstacle(&self, (x, y): (i32, i32)) -> bool {
+        if self.obstacles == 0 || y.rem_euclid(HEIGHT / 4) == 0 || self.portal((x, y)).is_some() {
+            return false;
+        }
+        let cell = (x as u32 as u64) << 32 | y as u32 as u64;
+        Rng::new(OBSTACLE_SEED ^ cell).next_u64() % 100 < u64::from(self.obstacles)
+    }
+
+    /// Moves from `a` to `b` on an empty board, taking the short way round
+    /// where it wraps.
+    pub fn distance(&self, a: (i32, i32), b: (i32, i32)) -> i32 {
+        let ((x0, y0), (x1, y1)) = self.arena();
+        let (mut dx, mut dy) = ((a.0 - b.0).abs(), (a.1 - b.1).abs());
+        if self.wrap.x {
+            dx = dx.min(x1 - x0 - dx);
+        }
+        if self.wrap.y {
+            dy = dy.min(y1 - y0 - dy);
+        }
+        dx + dy
+    }
+
+    /// Ticks to run per tick of the game clock.
+    pub fn speed(&self) -> f64 {
+        if self.mutators.double_speed {
+            2.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Clockwise quarter turns the board is shown at by `tick`.
+    pub fn quarter_turns(&self, tick: u32) -> u32 {
+        if self.mutators.rotate {
+            tick / ROTATE_TICKS % 4
+        } else {
+            0
+        }
+    }
+
+    /// The board heading for a direction pressed on the screen when the
+    /// board is shown turned by `turns` quarters.
+    pub fn unrotate(dir: (i32, i32), turns: u32) -> (i32, i32) {
+        (0..turns).fold(dir, |(x, y), _| (y, -x))
+    }
+
+    /// `pos` brought back inside the arena across the edges, walls or not.
+    pub fn wrap_pos(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        let ((x0, y0), (x1, y1)) = self.arena();
+        (
+            x0 + (x - x0).rem_euclid(x1 - x0),
+            y0 + (y - y0).rem_euclid(y1 - y0),
+        )
+    }
+
+    /// Where moving onto `pos` lands: brought back across the edges that
+    /// wrap and out of any portal, or `None` past a wall or into an
+    /// obstacle.
+    pub fn land(&self, (x, y): (i32, i32)) -> Option<(i32, i32)> {
+        let ((x0, y0), (x1, y1)) = self.arena();
+        let (wx, wy) = self.wrap_pos((x, y));
+        let x = if self.wrap.x { wx } else { x };
+        let y = if self.wrap.y { wy } else { y };
+        let inside = (x0..x1).contains(&x) && (y0..y1).contains(&y);
+        (inside && !self.obstacle((x, y))).then(|| self.through((x, y)))
+    }
+
+    /// `pos`, or where it comes out if it is a portal.
+    pub fn through(&self, pos: (i32, i32)) -> (i32, i32) {
+        self.portal(pos).unwrap_or(pos)
+    }
+
+    /// Whether stepping onto `pos` is deadly with food at `food`.
+    pub fn poisoned(&self, food: &[(i32, i32)], pos: (i32, i32)) -> bool {
+        if !self.mutators.poison {
+            return false;
+        }
+        let ((x0, y0), (x1, y1)) = self.arena();
+        food.iter().any(|food| {
+            let (mut dx, mut dy) = ((pos.0 - food.0).abs(), (pos.1 - food.1).abs());
+            if self.wrap.x {
+                dx = dx.min(x1 - x0 - dx);
+            }
+            if self.wrap.y {
+                dy = dy.min(y1 - y0 - dy);
+            }
+            dx == 1 && dy == 1
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snake {
+    pub body: VecDeque<(i32, i32)>,
+    pub dir: (i32, i32),
+    pub score: i32,
+    pub alive: bool,
+    #[serde(default)]
+    pub handicap: Handicap,
+    // progress toward the next move, in percent, for slowed-down snakes
+    #[serde(default)]
+    pace: u32,
+    #[serde(default)]
+    pub perks: Perks,
+    /// Crashes the snake can still survive this level.
+    #[serde(default)]
+    pub shields: u32,
+    /// Whether a bot is steering for the player.
+    #[serde(default)]
+    pub autopilot: bool,
+}
+
+impl Snake {
+    pub fn new(head: (i32, i32), dir: (i32, i32)) -> Self {
+        let mut body = VecDeque::new();
+        body.push_back(head);
+        Self {
+            body,
+            dir,
+            score: 0,
+            alive: true,
+            handicap: Handicap::default(),
+            pace: 0,
+            perks: Perks::default(),
+            shields: 0,
+            autopilot: false,
+        }
+    }
+
+    /// Adds the handicap's extra length behind the head, laid straight back
+    /// inside `rules`' arena. It never crosses an edge, not even one that
+    /// wraps, where it would start on the far side of the board; what
+    /// doesn't fit is piled on the last cell and unfurls as the snake moves.
+    pub fn with_handicap(mut self, handicap: Handicap, rules: &Rules) -> Self {
+        let mut tail = *self.body.back().unwrap();
+        for _ in 0..handicap.extra_length {
+            let behind = (tail.0 - self.dir.0, tail.1 - self.dir.1);
+            if rules.land(behind) == Some(behind) {
+                tail = behind;
+            }
+            self.body.push_back(tail);
+        }
+        self.handicap = handicap;
+        self
+    }
+
+    pub fn level(&self) -> i32 {
+        self.score / LEVEL_POINTS
+    }
+
+    /// Whether the snake moves this tick, given its slowdown.
+    fn ready(&mut self) -> bool {
+        self.pace += 100 - self.handicap.slowdown;
+        if self.pace < 100 {
+            return false;
+        }
+        self.pace -= 100;
+        true
+    }
+
+    fn turn(&mut self, dir: (i32, i32)) {
+        if dir != (-self.dir.0, -self.dir.1) {
+            self.dir = dir;
+        }
+    }
+}
+
+/// The simulated part of a game, free of any browser state.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Board {
+    pub snakes: Vec<Snake>,
+    #[serde(default)]
+    pub rules: Rules,
+    // eaten food is gone until the next `FoodSpawned`
+    #[serde(deserialize_with = "food")]
+    pub food: Vec<(i32, i32)>,
+    pub tick: u32,
+    pub over: bool,
+}
+
+impl Board {
+    pub fn new(snakes: Vec<Snake>, rules: Rules) -> Self {
+        Self {
+            snakes,
+            rules,
+            food: Vec::new(),
+            tick: 0,
+            over: false,
+        }
+    }
+
+    /// Rebuilds a board by applying `events` to a fresh one.
+    pub fn replay<'a>(
+        snakes: Vec<Snake>,
+        rules: Rules,
+        events: impl IntoIterator<Item = &'a Event>,
+    ) -> Self {
+        let mut board = Board::new(snakes, rules);
+        for event in events {
+            board.apply(event);
+        }
+        board
+    }
+
+    pub fn apply(&mut self, event: &Event) {
+        if self.over {
+            return;
+        }
+        match *event {
+            Event::TurnRequested { snake, dir } => {
+                if let Some(snake) = self.snakes.get_mut(snake) {
+                    snake.turn(dir);
+                }
+            }
+            Event::TickAdvanced => self.advance(),
+            Event::FoodSpawned { pos } => self.food.push(pos),
+            Event::ScoreAwarded { snake, points } => {
+                if let Some(snake) = self.snakes.get_mut(snake) {
+                    snake.score += points;
+                }
+            }
+            Event::PerkGained { snake, perk } => {
+                if let Some(snake) = self.snakes.get_mut(snake) {
+                    snake.perks.add(perk);
+                    if perk == Perk::Shield {
+                        snake.shields += 1;
+                    }
+                }
+            }
+            Event::AutopilotSet { snake, on } => {
+                if let Some(snake) = self.snakes.get_mut(snake) {
+                    snake.autopilot = on;
+                }
+            }
+        }
+    }
+
+    /// Everything on the board, walls and hazards first and food last.
+    pub fn entities(&self) -> Vec<Entity> {
+        let ((x0, y0), (x1, y1)) = self.rules.arena();
+        let mut entities = Vec::new();
+        for x in 0..WIDTH {
+            for y in 0..HEIGHT {
+                let outside = !((x0..x1).contains(&x) && (y0..y1).contains(&y));
+                let kind = if outside
+                    || self.rules.closed(self.tick, (x, y))
+                    || self.rules.obstacle((x, y))
+                {
+                    Kind::Wall
+                } else if self.rules.portal((x, y)).is_some() {
+                    Kind::Portal
+                } else if self.rules.poisoned(&self.food, (x, y)) {
+                    Kind::Poison
+                } else {
+                    continue;
+                };
+                entities.push(Entity { pos: (x, y), kind });
+            }
+        }
+        for (i, snake) in self.snakes.iter().enumerate() {
+            entities.extend(snake.body.iter().enumerate().map(|(k, &pos)| Entity {
+                pos,
+                kind: Kind::Segment {
+                    snake: i,
+                    head: k == 0,
+                },
+            }));
+        }
+        entities.extend(self.food.iter().map(|&pos| Entity {
+            pos,
+            kind: Kind::Food,
+        }));
+        entities
+    }
+
+    /// Whether `snake` ate in the tick that led here from `before`.
+    pub fn ate(&self, before: &Board, snake: usize) -> bool {
+        self.snakes[snake].body.len() > before.snakes[snake].body.len()
+    }
+
+    /// Whether `snake` died in the tick that led here from `before`.
+    pub fn died(&self, before: &Board, snake: usize) -> bool {
+        before.snakes[snake].alive && !self.snakes[snake].alive
+    }
+
+    /// Whether there is less food out than the rules call for, which
+    /// `spawn_food` tops up.
+    pub fn hungry(&self) -> bool {
+        !self.over && self.food.len() < self.rules.food_count as usize
+    }
+
+    /// The food closest to `snake`'s head, for bots to go after.
+    pub fn nearest_food(&self, snake: usize) -> Option<(i32, i32)> {
+        let head = *self.snakes[snake].body.front()?;
+        self.food
+            .iter()
+            .copied()
+            .min_by_key(|&food| self.rules.distance(head, food))
+    }
+
+    /// Picks where the next food appears, never on a portal or an
+    /// obstacle, where no head could stop to eat it, in a closed ring or on
+    /// other food. `None` once the board has all the food it calls for, or
+    /// when there is no cell left to put more on.
+    pub fn spawn_food(&self, rng: &mut Rng) -> Option<Event> {
+        let ((x0, y0), (x1, y1)) = self.rules.arena();
+        let free = |pos: (i32, i32)| {
+            self.rules.portal(pos).is_none()
+                && !self.rules.obstacle(pos)
+                && !self.rules.closed(self.tick, pos)
+                && !self.food.contains(&pos)
+        };
+        let room = (y0..y1).any(|y| (x0..x1).any(|x| free((x, y))));
+        if !self.hungry() || !room {
+            return None;
+        }
+        loop {
+            let pos = (x0 + rng.below(x1 - x0), y0 + rng.below(y1 - y0));
+            if free(pos) {
+                return Some(Event::FoodSpawned { pos });
+            }
+        }
+    }
+
+    /// Where `snake` lands moving one cell toward `dir`, or `None` if that
+    /// runs into a wall, poison or a closed ring.
+    pub fn target(&self, snake: &Snake, dir: (i32, i32)) -> Option<(i32, i32)> {
+        let head = snake.body.front().unwrap();
+        let pos = self.rules.land((head.0 + dir.0, head.1 + dir.1))?;
+        let deadly = self.rules.poisoned(&self.food, pos) || self.rules.closed(self.tick, pos);
+        (!deadly).then_some(pos)
+    }
+
+    fn advance(&mut self) {
+        self.tick += 1;
+        // food left in a ring as it closes is gone for good
+        let (rules, tick) = (self.rules, self.tick);
+        self.food.retain(|&pos| !rules.closed(tick, pos));
+        let moving: Vec<bool> = self
+            .snakes
+            .iter_mut()
+            .map(|s| s.alive && s.ready())
+            .collect();
+        // `None` for snakes sitting this tick out, `Some(None)` for one
+        // running into a wall
+        let mut targets: Vec<Option<Option<(i32, i32)>>> = self
+            .snakes
+            .iter()
+            .zip(moving)
+            .map(|(s, moving)| moving.then(|| self.target(s, s.dir)))
+            .collect();
+        for i in 0..targets.len() {
+            let crashed = match &targets[i] {
+                None => false,
+                Some(None) => true,
+                Some(Some(head)) => {
+                    let hit_body = self.snakes.iter().any(|s| s.body.contains(head));
+                    let hit_head = targets
+                        .iter()
+                        .enumerate()
+                        .any(|(j, t)| j != i && *t == Some(Some(*head)));
+                    hit_body || hit_head
+                }
+            };
+            if !crashed {
+                continue;
+            }
+            // a shield carries the snake straight on, through walls too
+            let snake = &self.snakes[i];
+            let head = snake.body.front().unwrap();
+            let through = self.rules.through(
+                self.rules
+                    .wrap_pos((head.0 + snake.dir.0, head.1 + snake.dir.1)),
+            );
+            let snake = &mut self.snakes[i];
+            if snake.shields > 0 {
+                snake.shields -= 1;
+                targets[i] = Some(Some(through));
+            } else {
+                snake.alive = false;
+            }
+        }
+        if self.rules.shrink.on() {
+            // the fallen stay on the board as they lay, and play goes on
+            // while two snakes are left
+            for (snake, target) in self.snakes.iter().zip(&mut targets) {
+                if !snake.alive {
+                    *target = None;
+                }
+            }
+            let alive = self.snakes.iter().filter(|s| s.alive).count();
+            if alive < self.snakes.len().min(2) {
+                self.over = true;
+                return;
+            }
+        } else if self.snakes.iter().any(|s| !s.alive) {
+            self.over = true;
+            return;
+        }
+        for (snake, head) in self.snakes.iter_mut().zip(targets) {
+            let Some(head) = head.flatten() else {
+                continue;
+            };
+            let reach = snake.perks.reach as i32;
+            let reached = self
+                .food
+                .iter()
+                .position(|&(x, y)| (x - head.0).abs().max((y - head.1).abs()) <= reach);
+            if let Some(food) = reached {
+                let level = snake.level();
+                snake.score += snake.handicap.multiplier as i32;
+                self.food.remove(food);
+                if snake.level() > level {
+                    snake.shields = snake.perks.shield;
+                }
+            } else {
+                snake.body.pop_back();
+            }
+            snake.body.push_front(head);
+        }
+    }
+}
+
+// Boards saved before there could be more than one food hold it as a lone
+// position, or null.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FoodRepr {
+    One(Option<(i32, i32)>),
+    Many(Vec<(i32, i32)>),
+}
+
+fn food<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<(i32, i32)>, D::Error> {
+    Ok(match FoodRepr::deserialize(deserializer)? {
+        FoodRepr::One(pos) => pos.into_iter().collect(),
+        FoodRepr::Many(food) => food,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn walls() -> Rules {
+        Rules {
+            wrap: WrapConfig::both(false),
+            ..Rules::default()
+        }
+    }
+
+    #[test]
+    fn moves_one_cell_per_tick() {
+        let mut b = Board::new(vec![Snake::new((5, 5), (1, 0))], Rules::default());
+        b.apply(&Event::TickAdvanced);
+        assert_eq!(b.snakes[0].body.front(), Some(&(6, 5)));
+        assert_eq!(b.tick, 1);
+    }
+
+    #[test]
+    fn ignores_turning_back() {
+        let mut b = Board::new(vec![Snake::new((5, 5), (1, 0))], Rules::default());
+        b.apply(&Event::TurnRequested {
+            snake: 0,
+            dir: (-1, 0),
+        });
+        b.apply(&Event::TickAdvanced);
+        assert_eq!(b.snakes[0].body.front(), Some(&(6, 5)));
+    }
+
+    #[test]
+    fn eats_and_grows() {
+        let mut b = Board::new(vec![Snake::new((5, 5), (1, 0))], Rules::default());
+        b.apply(&Event::FoodSpawned { pos: (6, 5) });
+        b.apply(&Event::TickAdvanced);
+        assert_eq!(b.snakes[0].score, 1);
+        assert_eq!(b.snakes[0].body.len(), 2);
+        assert!(b.food.is_empty());
+    }
+
+    #[test]
+    fn wraps_across_the_edges() {
+        let mut b = Board::new(vec![Snake::new((WIDTH - 1, 0), (1, 0))], Rules::default());
+        b.apply(&Event::TickAdvanced);
+        assert_eq!(b.snakes[0].body.front(), Some(&(0, 0)));
+        b.apply(&Event::TurnRequested {
+            snake: 0,
+            dir: (0, -1),
+        });
+        b.apply(&Event::TickAdvanced);
+        assert_eq!(b.snakes[0].body.front(), Some(&(0, HEIGHT - 1)));
+        assert!(!b.over);
+    }
+
+    #[test]
+    fn crashes_into_walls() {
+        let mut b = Board::new(vec![Snake::new((WIDTH - 1, 0), (1, 0))], walls());
+        b.apply(&Event::TickAdvanced);
+        assert!(!b.snakes[0].alive);
+        assert!(b.over);
+        // a finished board takes no more events
+        b.apply(&Event::TickAdvanced);
+        assert_eq!(b.tick, 1);
+    }
+
+    #[test]
+    fn wraps_one_axis_only() {
+        let rules = Rules {
+            wrap: WrapConfig { x: true, y: false },
+            ..Rules::default()
+        };
+        assert_eq!(rules.land((WIDTH, 3)), Some((0, 3)));
+        assert_eq!(rules.land((3, -1)), None);
+    }
+
+    #[test]
+    fn crashes_into_bodies() {
+        let me = Snake::new((5, 5), (1, 0));
+        let mut rival = Snake::new((6, 4), (0, -1));
+        rival.body.extend([(6, 5), (6, 6)]);
+        let mut b = Board::new(vec![me, rival], Rules::default());
+        b.apply(&Event::TickAdvanced);
+        assert!(!b.snakes[0].alive);
+        assert!(b.snakes[1].alive);
+        assert!(b.over);
+    }
+
+    #[test]
+    fn crashes_head_on() {
+        let me = Snake::new((5, 5), (1, 0));
+        let rival = Snake::new((7, 5), (-1, 0));
+        let mut b = Board::new(vec![me, rival], Rules::default());
+        b.apply(&Event::TickAdvanced);
+        // both heads make for (6, 5)
+        assert!(!b.snakes[0].alive);
+        assert!(!b.snakes[1].alive);
+    }
+
+    #[test]
+    fn crashes_into_itself() {
+        let mut me = Snake::new((5, 5), (0, 1));
+        me.body.extend([(5, 4), (6, 4), (6, 5), (6, 6)]);
+        let mut b = Board::new(vec![me], Rules::default());
+        b.apply(&Event::TurnRequested {
+            snake: 0,
+            dir: (1, 0),
+        });
+        b.apply(&Event::TickAdvanced);
+        // (6, 5) is still body when the head gets there
+        assert!(b.over);
+    }
+
+    #[test]
+    fn replays_the_same_board() {
+        let events = [
+            Event::FoodSpawned { pos: (7, 5) },
+            Event::TickAdvanced,
+            Event::TickAdvanced,
+            Event::TurnRequested {
+                snake: 0,
+                dir: (0, 1),
+            },
+            Event::TickAdvanced,
+        ];
+        let b = Board::replay(vec![Snake::new((5, 5), (1, 0))], Rules::default(), &events);
+        assert_eq!(b.snakes[0].score, 1);
+        assert_eq!(
+            b.snakes[0].body.iter().copied().collect::<Vec<_>>(),
+            [(7, 6), (7, 5)]
+        );
+    }
+
+    #[test]
+    fn lays_handicaps_inside_the_arena() {
+        let handicap = Handicap {
+            extra_length: 10,
+            ..Handicap::default()
+        };
+        for rules in [
+            Rules::default(),
+            walls(),
+            Rules::decode("walls+tiny").unwrap(),
+        ] {
+            let snake = Snake::new((WIDTH / 4, HEIGHT / 2), (1, 0)).with_handicap(handicap, &rules);
+            assert_eq!(snake.body.len(), 11);
+            let ((x0, _), _) = rules.arena();
+            assert!(snake.body.iter().all(|&(x, _)| x >= x0 && x <= WIDTH / 4));
+        }
+    }
+
+    #[test]
+    fn rules_round_trip() {
+        for text in [
+            "wrap",
+            "walls",
+            "wrap_x",
+            "wrap_y",
+            "walls+poison+tiny",
+            "wrap+portals+shrink:40:6",
+            "walls+food:3+obstacles:10",
+        ] {
+            let rules = Rules::decode(text).unwrap();
+            assert_eq!(rules.encode(), text);
+        }
+        assert!(Rules::decode("sideways").is_none());
+        assert!(Rules::decode("wrap+nonsense").is_none());
+        assert!(Rules::decode("wrap+food:9").is_none());
+    }
+
+    #[test]
+    fn reads_old_wrap_flags() {
+        let both: WrapConfig = serde_json::from_str("false").unwrap();
+        assert_eq!(both, WrapConfig::both(false));
+        let axes: WrapConfig = serde_json::from_str(r#"{"x":true,"y":false}"#).unwrap();
+        assert_eq!(axes, WrapConfig { x: true, y: false });
+        let json = serde_json::to_string(&axes).unwrap();
+        assert_eq!(serde_json::from_str::<WrapConfig>(&json).unwrap(), axes);
+    }
+
+    #[test]
+    fn reads_old_single_food() {
+        let old = r#"{"snakes":[],"food":[3,4],"tick":0,"over":false}"#;
+        assert_eq!(serde_json::from_str::<Board>(old).unwrap().food, [(3, 4)]);
+        let eaten = r#"{"snakes":[],"food":null,"tick":0,"over":false}"#;
+        assert!(serde_json::from_str::<Board>(eaten)
+            .unwrap()
+            .food
+            .is_empty());
+    }
+
+    #[test]
+    fn keeps_obstacles_off_the_start_rows() {
+        let rules = Rules::decode("walls+obstacles:20").unwrap();
+        for x in 0..WIDTH {
+            assert!(!rules.obstacle((x, HEIGHT / 2)));
+            assert!(!rules.obstacle((x, HEIGHT / 4)));
+        }
+        assert!((0..WIDTH).any(|x| (0..HEIGHT).any(|y| rules.obstacle((x, y)))));
+    }
+
+    #[test]
+    fn stops_spawning_food_when_the_board_is_full() {
+        let rules = Rules {
+            food_count: 1000,
+            ..Rules::default()
+        };
+        let mut b = Board::new(vec![Snake::new((5, 5), (1, 0))], rules);
+        let mut rng = Rng::new(1);
+        while let Some(food) = b.spawn_food(&mut rng) {
+            b.apply(&food);
+        }
+        assert_eq!(b.food.len(), (WIDTH * HEIGHT) as usize);
+        assert!(b.hungry());
+        assert!(Rules::decode(&rules.encode()).is_none());
+    }
+}
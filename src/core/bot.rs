@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::board::Board;
+use crate::core::rng::Rng;
+use crate::core::{cycle, path};
+
+const DIRS: [(i32, i32); 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+// ticks a wanderer keeps to one whim before picking another
+const WHIM_TICKS: u32 = 8;
+
+/// How a bot weighs its moves. Every personality heads for the food and
+/// steers clear of anything deadly; they differ in what else they count.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Personality {
+    /// The shortest way to the food and nothing else.
+    #[default]
+    Greedy,
+    /// Keeps to open ground, taking the long way round rather than a tight
+    /// spot.
+    Cautious,
+    /// Cuts across the rival's path to box it in.
+    Blocker,
+    /// Ambles towards the food, wandering off on a whim.
+    Wanderer,
+    /// Follows a path through every cell of the arena, cutting corners
+    /// while it is short, so it never traps itself. Arenas without such a
+    /// path get the greedy bot instead.
+    Cycle,
+    /// Plans the whole way to the food with A*, around bodies and walls,
+    /// and plays greedy when there is no way through.
+    Planner,
+}
+
+impl Personality {
+    pub const ALL: [Personality; 6] = [
+        Personality::Greedy,
+        Personality::Cautious,
+        Personality::Blocker,
+        Personality::Wanderer,
+        Personality::Cycle,
+        Personality::Planner,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Personality::Greedy => "greedy",
+            Personality::Cautious => "cautious",
+            Personality::Blocker => "blocker",
+            Personality::Wanderer => "wanderer",
+            Personality::Cycle => "cycle",
+            Personality::Planner => "planner",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.name() == name)
+    }
+
+    // What moving the head to `head` costs; the cheapest move wins.
+    fn cost(self, board: &Board, me: usize, head: (i32, i32), food: (i32, i32), dir: usize) -> i32 {
+        let to_food = board.rules.distance(head, food);
+        match self {
+            Personality::Greedy | Personality::Cycle | Personality::Planner => to_food,
+            Personality::Cautious => to_food + 8 * (4 - free_around(board, head)),
+            Personality::Blocker => match rival_head(board, me) {
+                Some(rival) => to_food + 3 * board.rules.distance(head, rival),
+                None => to_food,
+            },
+            Personality::Wanderer => {
+                let whim = u64::from(board.tick / WHIM_TICKS) << 8 | (me as u64) << 2 | dir as u64;
+                to_food + Rng::new(whim).below(12)
+            }
+        }
+    }
+}
+
+/// Heads for the food by the shortest distance, preferring any move that
+/// doesn't run into a snake or a wall.
+pub fn greedy(board: &Board, me: usize, food: (i32, i32)) -> (i32, i32) {
+    steer(board, me, food, Personality::Greedy)
+}
+
+/// The move `personality` makes towards the food, preferring any that
+/// doesn't run into a snake or a wall. The same board always gets the same
+/// move, so bots play alike on every peer and in replays.
+pub fn steer(board: &Board, me: usize, food: (i32, i32), personality: Personality) -> (i32, i32) {
+    let planned = match personality {
+        Personality::Cycle => cycle::steer(board, me, food),
+        Personality::Planner => path::next(board, me, food),
+        _ => None,
+    };
+    if let Some(dir) = planned {
+        return dir;
+    }
+    let snake = &board.snakes[me];
+    DIRS.into_iter()
+        .enumerate()
+        .filter(|&(_, dir)| dir != (-snake.dir.0, -snake.dir.1))
+        .min_by_key(|&(i, dir)| match board.target(snake, dir) {
+            Some(head) => (
+                blocked(board, head),
+                personality.cost(board, me, head, food, i),
+            ),
+            None => (true, i32::MAX),
+        })
+        .map_or(snake.dir, |(_, dir)| dir)
+}
+
+/// How many ways the snake's head could move next without running into
+/// anything.
+pub fn exits(board: &Board, me: usize) -> usize {
+    let snake = &board.snakes[me];
+    DIRS.into_iter()
+        .filter_map(|dir| board.target(snake, dir))
+        .filter(|&head| !blocked(board, head))
+        .count()
+}
+
+fn blocked(board: &Board, pos: (i32, i32)) -> bool {
+    board
+        .entities()
+        .iter()
+        .any(|e| e.pos == pos && e.kind.deadly())
+}
+
+// How many of the cells next to `pos` are clear.
+fn free_around(board: &Board, pos: (i32, i32)) -> i32 {
+    DIRS.into_iter()
+        .filter_map(|(dx, dy)| board.rules.land((pos.0 + dx, pos.1 + dy)))
+        .filter(|&p| !blocked(board, p))
+        .count() as i32
+}
+
+// Where the nearest other living snake's head will be after its next move.
+fn rival_head(board: &Board, me: usize) -> Option<(i32, i32)> {
+    let head = *board.snakes[me].body.front()?;
+    board
+        .snakes
+        .iter()
+        .enumerate()
+        .filter(|&(i, s)| i != me && s.alive)
+        .filter_map(|(_, s)| board.target(s, s.dir))
+        .min_by_key(|&pos| board.rules.distance(head, pos))
+}
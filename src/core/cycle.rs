@@ -0,0 +1,103 @@
+use crate::core::board::{Board, Rules};
+
+// cells kept free between the head and the tail when cutting a corner, so
+// the tail staying put while the snake grows can't close the gap
+const SLACK: usize = 4;
+
+/// A Hamiltonian cycle: a closed path through every cell of the arena. A
+/// snake that only ever follows it can't trap itself, however long it gets.
+pub struct Cycle {
+    origin: (i32, i32),
+    size: (i32, i32),
+    // each cell's place along the cycle, row by row from `origin`
+    order: Vec<usize>,
+}
+
+impl Cycle {
+    /// The cycle for `rules`, or `None` if the arena doesn't have one: when
+    /// both sides are odd, or portals, obstacles or a shrinking arena change
+    /// which cells follow which.
+    pub fn new(rules: &Rules) -> Option<Self> {
+        if rules.mutators.portals || rules.obstacles > 0 || rules.shrink.on() {
+            return None;
+        }
+        let (origin, (x1, y1)) = rules.arena();
+        let size = (x1 - origin.0, y1 - origin.1);
+        // rows sweep across and back, leaving the first column free for
+        // the way home; an odd number of rows is swept as columns instead
+        let transposed = size.1 % 2 == 1;
+        let (across, down) = if transposed { (size.1, size.0) } else { size };
+        if down % 2 == 1 || across < 2 {
+            return None;
+        }
+        let mut path = Vec::with_capacity((across * down) as usize);
+        for row in 0..down {
+            let sweep: Vec<i32> = if row % 2 == 0 {
+                (1..across).collect()
+            } else {
+                (1..across).rev().collect()
+            };
+            path.extend(sweep.into_iter().map(|col| (col, row)));
+        }
+        path.extend((0..down).rev().map(|row| (0, row)));
+        let mut order = vec![0; path.len()];
+        for (i, (col, row)) in path.into_iter().enumerate() {
+            let (x, y) = if transposed { (row, col) } else { (col, row) };
+            order[(y * size.0 + x) as usize] = i;
+        }
+        Some(Self {
+            origin,
+            size,
+            order,
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    fn place(&self, (x, y): (i32, i32)) -> Option<usize> {
+        let (x, y) = (x - self.origin.0, y - self.origin.1);
+        let inside = (0..self.size.0).contains(&x) && (0..self.size.1).contains(&y);
+        inside.then(|| self.order[(y * self.size.0 + x) as usize])
+    }
+
+    // How many steps along the cycle it is from `from` to `to`.
+    fn ahead(&self, from: usize, to: usize) -> usize {
+        (to + self.len() - from) % self.len()
+    }
+}
+
+/// The move that keeps snake `me` on the cycle, cutting ahead towards the
+/// food while the snake is short enough to leave the room. `None` when the
+/// board has no cycle or the way along it is blocked, as it can be for a
+/// snake that didn't start out on it.
+pub fn steer(board: &Board, me: usize, food: (i32, i32)) -> Option<(i32, i32)> {
+    let cycle = Cycle::new(&board.rules)?;
+    let snake = &board.snakes[me];
+    let head = *snake.body.front()?;
+    let at = cycle.place(head)?;
+    let tail = cycle.place(*snake.body.back()?)?;
+    let room = match cycle.ahead(at, tail) {
+        0 => cycle.len(),
+        room => room,
+    };
+    // corners are only cut while the snake fills less than half the board
+    let reach = if snake.body.len() * 2 < cycle.len() {
+        let to_food = cycle.place(food).map_or(1, |f| cycle.ahead(at, f));
+        room.saturating_sub(SLACK).min(to_food).max(1)
+    } else {
+        1
+    };
+    [(0, -1), (0, 1), (-1, 0), (1, 0)]
+        .into_iter()
+        .filter_map(|dir| {
+            let next = (head.0 + dir.0, head.1 + dir.1);
+            let step = cycle.ahead(at, cycle.place(next)?);
+            let clear = board.target(snake, dir) == Some(next)
+                && !board.snakes.iter().any(|s| s.body.contains(&next));
+            ((1..=reach).contains(&step) && clear).then_some((step, dir))
+        })
+        .max_by_key(|&(step, _)| step)
+        .map(|(_, dir)| dir)
+}
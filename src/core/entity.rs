@@ -0,0 +1,30 @@
+/// Something on a board cell. Boards keep snakes and food in their own
+/// fields for the rules and the save format, and `Board::entities` lays
+/// them out as one flat list so drawing and bots can walk everything on the
+/// board alike.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Entity {
+    pub pos: (i32, i32),
+    pub kind: Kind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Segment {
+        snake: usize,
+        head: bool,
+    },
+    Food,
+    Poison,
+    /// A cell outside the arena.
+    Wall,
+    /// One end of a pair of portals.
+    Portal,
+}
+
+impl Kind {
+    /// Whether a head moving onto it crashes.
+    pub fn deadly(self) -> bool {
+        !matches!(self, Kind::Food | Kind::Portal)
+    }
+}
@@ -0,0 +1,101 @@
+use serde::Serialize;
+
+use crate::core::batch::BatchSim;
+use crate::core::board::Rules;
+use crate::core::entity::{Entity, Kind};
+use crate::core::{HEIGHT, WIDTH};
+
+/// Headings for the actions, numbered clockwise from up.
+pub const ACTIONS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+// what crashing costs an agent, against a point for each food
+const DEATH_REWARD: f64 = -1.0;
+
+// what each cell of an observation's grid holds
+const EMPTY: u8 = 0;
+const BODY: u8 = 1;
+const HEAD: u8 = 2;
+const FOOD: u8 = 3;
+const DEADLY: u8 = 4;
+const PORTAL: u8 = 5;
+
+/// One single-player game behind a gym-style interface, for training
+/// agents against the game's own rules without drawing anything.
+pub struct Env {
+    sim: BatchSim,
+}
+
+/// What an agent sees of the board.
+#[derive(Serialize)]
+pub struct Observation {
+    pub width: i32,
+    pub height: i32,
+    /// The board row by row: 0 empty, 1 body, 2 head, 3 food, 4 anything
+    /// deadly (walls, poison, closed rings) and 5 a portal.
+    pub grid: Vec<u8>,
+    pub head: (i32, i32),
+    pub dir: (i32, i32),
+    /// Every piece of food out, more than one when the rules call for it.
+    pub food: Vec<(i32, i32)>,
+    pub score: i32,
+    pub tick: u32,
+}
+
+/// What one action led to.
+#[derive(Serialize)]
+pub struct Step {
+    pub observation: Observation,
+    /// The points scored, or -1 for crashing.
+    pub reward: f64,
+    pub done: bool,
+}
+
+impl Env {
+    /// A new game on `seed`, which always plays the same food.
+    pub fn new(seed: u64, rules: Rules) -> Self {
+        Self {
+            sim: BatchSim::new(1, seed, rules),
+        }
+    }
+
+    /// Turns towards `ACTIONS[action]` and runs a tick. A finished game
+    /// stays finished, for no reward.
+    pub fn step(&mut self, action: usize) -> Step {
+        let was_over = self.sim.board(0).over;
+        let outcome = self.sim.step(&[ACTIONS[action]])[0];
+        let reward = if outcome.over && !was_over {
+            DEATH_REWARD
+        } else {
+            f64::from(outcome.reward)
+        };
+        Step {
+            observation: self.observe(),
+            reward,
+            done: outcome.over,
+        }
+    }
+
+    pub fn observe(&self) -> Observation {
+        let board = self.sim.board(0);
+        let snake = &board.snakes[0];
+        let mut grid = vec![EMPTY; (WIDTH * HEIGHT) as usize];
+        for Entity { pos: (x, y), kind } in board.entities() {
+            grid[(y * WIDTH + x) as usize] = match kind {
+                Kind::Segment { head: true, .. } => HEAD,
+                Kind::Segment { .. } => BODY,
+                Kind::Food => FOOD,
+                Kind::Portal => PORTAL,
+                Kind::Wall | Kind::Poison => DEADLY,
+            };
+        }
+        Observation {
+            width: WIDTH,
+            height: HEIGHT,
+            grid,
+            head: snake.body[0],
+            dir: snake.dir,
+            food: board.food.clone(),
+            score: snake.score,
+            tick: board.tick,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+pub const MAX_SLOWDOWN: u32 = 50;
+pub const MAX_EXTRA_LENGTH: u32 = 10;
+pub const MAX_MULTIPLIER: u32 = 5;
+
+/// Per-player rule modifiers for evening out mixed-skill games.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Handicap {
+    /// Percentage of ticks on which the snake stands still.
+    pub slowdown: u32,
+    /// Segments added behind the head at the start of a run.
+    pub extra_length: u32,
+    /// Points awarded per food.
+    pub multiplier: u32,
+}
+
+impl Default for Handicap {
+    fn default() -> Self {
+        Self {
+            slowdown: 0,
+            extra_length: 0,
+            multiplier: 1,
+        }
+    }
+}
+
+impl Handicap {
+    /// Short description for results, empty when there is no handicap.
+    pub fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if self.slowdown > 0 {
+            parts.push(format!("-{}% speed", self.slowdown));
+        }
+        if self.extra_length > 0 {
+            parts.push(format!("+{} length", self.extra_length));
+        }
+        if self.multiplier > 1 {
+            parts.push(format!("\u{d7}{}", self.multiplier));
+        }
+        if parts.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", parts.join(", "))
+        }
+    }
+
+    pub fn valid(&self) -> bool {
+        self.slowdown <= MAX_SLOWDOWN
+            && self.extra_length <= MAX_EXTRA_LENGTH
+            && (1..=MAX_MULTIPLIER).contains(&self.multiplier)
+    }
+}
@@ -0,0 +1,96 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::board::{Board, Event, Rules, Snake};
+use crate::core::bot::{self, Personality};
+use crate::core::handicap::Handicap;
+use crate::core::rng::Rng;
+use crate::core::{HEIGHT, WIDTH};
+
+const MAX_TICKS: u32 = 10_000;
+
+/// How a headless match is set up, matching a versus round in the browser.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MatchConfig {
+    pub seed: u64,
+    #[serde(default)]
+    pub rules: Rules,
+    #[serde(default)]
+    pub handicaps: [Handicap; 2],
+    /// A bot to play either side in place of its inputs.
+    #[serde(default)]
+    pub bots: [Option<Personality>; 2],
+    /// Ticks after which the match stops even if both snakes live.
+    #[serde(default = "max_ticks")]
+    pub max_ticks: u32,
+}
+
+fn max_ticks() -> u32 {
+    MAX_TICKS
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MatchResult {
+    pub scores: [i32; 2],
+    pub alive: [bool; 2],
+    pub ticks: u32,
+    /// The survivor if only one snake lives, otherwise the higher scorer;
+    /// `None` on a tie.
+    pub winner: Option<usize>,
+}
+
+/// The two snakes of a versus round on `rules`, facing each other.
+pub fn versus_snakes(handicaps: [Handicap; 2], rules: &Rules) -> Vec<Snake> {
+    vec![
+        Snake::new((WIDTH / 4, HEIGHT / 2), (1, 0)).with_handicap(handicaps[0], rules),
+        Snake::new((WIDTH * 3 / 4, HEIGHT / 2), (-1, 0)).with_handicap(handicaps[1], rules),
+    ]
+}
+
+/// Plays a versus round with no browser, for servers that need the
+/// authoritative result and for bots. `inputs_a[t]` and `inputs_b[t]` are
+/// each snake's heading for tick `t`; a snake whose inputs run out keeps
+/// its heading, and one given a bot in `config` ignores its inputs.
+pub fn run_match(
+    inputs_a: &[(i32, i32)],
+    inputs_b: &[(i32, i32)],
+    config: &MatchConfig,
+) -> MatchResult {
+    let mut rng = Rng::new(config.seed);
+    let mut board = Board::new(versus_snakes(config.handicaps, &config.rules), config.rules);
+    while let Some(food) = board.spawn_food(&mut rng) {
+        board.apply(&food);
+    }
+    while !board.over && board.tick < config.max_ticks {
+        let t = board.tick as usize;
+        for (snake, inputs) in [inputs_a, inputs_b].into_iter().enumerate() {
+            let bot = config.bots[snake].zip(board.nearest_food(snake));
+            let input = match bot {
+                Some((personality, food)) => Some(bot::steer(&board, snake, food, personality)),
+                None => inputs.get(t).copied(),
+            };
+            if let Some(dir) = input {
+                board.apply(&Event::TurnRequested { snake, dir });
+            }
+        }
+        board.apply(&Event::TickAdvanced);
+        while let Some(food) = board.spawn_food(&mut rng) {
+            board.apply(&food);
+        }
+    }
+    let scores = [board.snakes[0].score, board.snakes[1].score];
+    let alive = [board.snakes[0].alive, board.snakes[1].alive];
+    let standing = |i: usize| (alive[i], scores[i]);
+    let winner = match standing(0).cmp(&standing(1)) {
+        Ordering::Greater => Some(0),
+        Ordering::Less => Some(1),
+        Ordering::Equal => None,
+    };
+    MatchResult {
+        scores,
+        alive,
+        ticks: board.tick,
+        winner,
+    }
+}
@@ -0,0 +1,28 @@
+use crate::core::board::Snake;
+
+// how far past the latest tick a stalled snake keeps gliding, in ticks
+const MAX_EXTRAPOLATION: f64 = 1.0;
+
+/// Progress from the previous tick toward the next, in ticks. Past 1 the
+/// snake is extrapolated, up to `MAX_EXTRAPOLATION` ticks.
+pub fn progress(since_tick_ms: f64, tick_ms: f64) -> f64 {
+    (since_tick_ms / tick_ms).clamp(0.0, 1.0 + MAX_EXTRAPOLATION)
+}
+
+/// Segment positions in cells, blended from the snake one tick ago to the
+/// snake now. Segments that wrapped across an edge jump instead of sliding
+/// across the board.
+pub fn segments(prev: &Snake, now: &Snake, t: f64) -> Vec<(f64, f64)> {
+    now.body
+        .iter()
+        .enumerate()
+        .map(|(k, &(x, y))| {
+            let (px, py) = prev.body.get(k).copied().unwrap_or((x, y));
+            let (dx, dy) = (x - px, y - py);
+            if dx.abs() > 1 || dy.abs() > 1 {
+                return (x as f64, y as f64);
+            }
+            (px as f64 + dx as f64 * t, py as f64 + dy as f64 * t)
+        })
+        .collect()
+}
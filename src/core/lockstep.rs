@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+
+// ticks between a key press and the tick it applies to, hiding one-way latency
+const INPUT_DELAY: u32 = 3;
+
+/// Input exchange for two peers running the same simulation. A tick only
+/// advances once both players' headings for it are known.
+pub struct Lockstep {
+    pub local: usize,
+    tick: u32,
+    sent: u32,
+    inputs: [BTreeMap<u32, (i32, i32)>; 2],
+}
+
+impl Lockstep {
+    pub fn new(local: usize) -> Self {
+        Self {
+            local,
+            tick: 0,
+            sent: 0,
+            inputs: [BTreeMap::new(), BTreeMap::new()],
+        }
+    }
+
+    /// Commits the local heading to the next unscheduled tick, returning the
+    /// tick and heading to send to the peer.
+    pub fn schedule(&mut self, dir: (i32, i32)) -> Option<(u32, (i32, i32))> {
+        if self.sent >= self.tick + INPUT_DELAY {
+            return None;
+        }
+        let tick = self.sent;
+        self.sent += 1;
+        self.inputs[self.local].insert(tick, dir);
+        Some((tick, dir))
+    }
+
+    /// Holds the peer's heading for `tick`. Ticks already played, or
+    /// further ahead than the peer could have scheduled, are dropped: the
+    /// peer can't get past a tick this side hasn't sent, so it runs at most
+    /// `INPUT_DELAY` ticks ahead and schedules `INPUT_DELAY` beyond that.
+    pub fn receive(&mut self, tick: u32, dir: (i32, i32)) {
+        if (self.tick..self.tick + 2 * INPUT_DELAY).contains(&tick) {
+            self.inputs[1 - self.local].insert(tick, dir);
+        }
+    }
+
+    /// Both players' headings for the current tick, if they have arrived.
+    pub fn advance(&mut self) -> Option<[(i32, i32); 2]> {
+        let a = *self.inputs[0].get(&self.tick)?;
+        let b = *self.inputs[1].get(&self.tick)?;
+        self.inputs[0].remove(&self.tick);
+        self.inputs[1].remove(&self.tick);
+        self.tick += 1;
+        Some([a, b])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waits_for_both_sides() {
+        let mut l = Lockstep::new(0);
+        assert_eq!(l.schedule((1, 0)), Some((0, (1, 0))));
+        assert_eq!(l.advance(), None);
+        l.receive(0, (0, 1));
+        assert_eq!(l.advance(), Some([(1, 0), (0, 1)]));
+        assert_eq!(l.advance(), None);
+    }
+
+    #[test]
+    fn orders_the_guest_second() {
+        let mut l = Lockstep::new(1);
+        l.schedule((0, -1));
+        l.receive(0, (1, 0));
+        assert_eq!(l.advance(), Some([(1, 0), (0, -1)]));
+    }
+
+    #[test]
+    fn schedules_at_most_the_input_delay_ahead() {
+        let mut l = Lockstep::new(0);
+        for tick in 0..INPUT_DELAY {
+            assert_eq!(l.schedule((1, 0)), Some((tick, (1, 0))));
+        }
+        assert_eq!(l.schedule((1, 0)), None);
+        l.receive(0, (1, 0));
+        l.advance();
+        assert_eq!(l.schedule((1, 0)), Some((INPUT_DELAY, (1, 0))));
+    }
+
+    #[test]
+    fn holds_early_inputs_until_their_tick() {
+        let mut l = Lockstep::new(0);
+        l.receive(1, (0, 1));
+        l.receive(0, (-1, 0));
+        l.schedule((1, 0));
+        l.schedule((1, 0));
+        assert_eq!(l.advance(), Some([(1, 0), (-1, 0)]));
+        assert_eq!(l.advance(), Some([(1, 0), (0, 1)]));
+    }
+
+    #[test]
+    fn drops_stale_and_far_off_inputs() {
+        let mut l = Lockstep::new(0);
+        l.schedule((1, 0));
+        l.receive(0, (0, 1));
+        l.advance();
+        l.receive(0, (0, -1));
+        l.receive(1 + 2 * INPUT_DELAY, (0, -1));
+        assert!(l.inputs[1].is_empty());
+        l.receive(2 * INPUT_DELAY, (0, -1));
+        assert_eq!(l.inputs[1].len(), 1);
+    }
+}
@@ -0,0 +1,19 @@
+pub mod batch;
+pub mod board;
+pub mod bot;
+pub mod cycle;
+pub mod entity;
+pub mod env;
+pub mod handicap;
+pub mod headless;
+pub mod interp;
+pub mod lockstep;
+pub mod names;
+pub mod path;
+pub mod perk;
+pub mod plugin;
+pub mod replay;
+pub mod rng;
+
+pub const WIDTH: i32 = 20;
+pub const HEIGHT: i32 = 20;
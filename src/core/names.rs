@@ -0,0 +1,59 @@
+use crate::core::rng::Rng;
+
+// (word, weight); the plainer words come up more often, so the rare ones
+// still feel like a find
+const ADJECTIVES: [(&str, u32); 16] = [
+    ("Swift", 6),
+    ("Sneaky", 6),
+    ("Hungry", 6),
+    ("Sleepy", 5),
+    ("Lucky", 5),
+    ("Mighty", 4),
+    ("Turbo", 4),
+    ("Fuzzy", 4),
+    ("Cosmic", 3),
+    ("Grumpy", 3),
+    ("Jolly", 3),
+    ("Neon", 2),
+    ("Quantum", 2),
+    ("Legendary", 1),
+    ("Majestic", 1),
+    ("Radioactive", 1),
+];
+
+const SNAKES: [(&str, u32); 14] = [
+    ("Snake", 6),
+    ("Viper", 5),
+    ("Python", 5),
+    ("Cobra", 5),
+    ("Adder", 4),
+    ("Asp", 4),
+    ("Mamba", 3),
+    ("Boa", 3),
+    ("Rattler", 3),
+    ("Krait", 2),
+    ("Taipan", 2),
+    ("Anaconda", 2),
+    ("Basilisk", 1),
+    ("Sea Serpent", 1),
+];
+
+fn pick(rng: &mut Rng, words: &[(&'static str, u32)]) -> &'static str {
+    let total: u32 = words.iter().map(|&(_, w)| w).sum();
+    let mut roll = rng.below(total as i32) as u32;
+    for &(word, weight) in words {
+        if roll < weight {
+            return word;
+        }
+        roll -= weight;
+    }
+    words[0].0
+}
+
+/// A name such as "Turbo Cobra 42", the same for the same `rng` state.
+/// Guests without initials go by one, and so do bots.
+pub fn generate(rng: &mut Rng) -> String {
+    let adjective = pick(rng, &ADJECTIVES);
+    let snake = pick(rng, &SNAKES);
+    format!("{adjective} {snake} {}", 1 + rng.below(99))
+}
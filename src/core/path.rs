@@ -0,0 +1,83 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::core::board::Board;
+
+const DIRS: [(i32, i32); 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+
+/// The shortest way for snake `me` to `goal` by A*, as the headings to
+/// take one move at a time, or `None` if there is no way through. Moves
+/// cross the edges that wrap and go through portals; walls, poison, closed
+/// rings and bodies are avoided, a body only until its end has moved off
+/// the cell by the time the head would get there.
+pub fn plan(board: &Board, me: usize, goal: (i32, i32)) -> Option<Vec<(i32, i32)>> {
+    let snake = &board.snakes[me];
+    let start = *snake.body.front()?;
+    // how many moves a body cell stays taken: the head only crashes into
+    // a tail that is still there when it arrives
+    let mut frees = HashMap::new();
+    for s in &board.snakes {
+        let len = s.body.len();
+        for (i, &pos) in s.body.iter().enumerate() {
+            let wait = if s.alive { len - i } else { usize::MAX };
+            let slot = frees.entry(pos).or_insert(0);
+            *slot = wait.max(*slot);
+        }
+    }
+    // the snake can't double back on its first move
+    let back = (-snake.dir.0, -snake.dir.1);
+    // each cell reached, with the cell before it and the heading between
+    let mut from = HashMap::new();
+    let mut cost = HashMap::from([(start, 0usize)]);
+    let mut open = BinaryHeap::from([(Reverse(estimate(board, start, goal)), 0usize, start)]);
+    while let Some((_, moves, pos)) = open.pop() {
+        if pos == goal {
+            let mut dirs = Vec::new();
+            let mut at = pos;
+            while let Some(&(prev, dir)) = from.get(&at) {
+                dirs.push(dir);
+                at = prev;
+            }
+            dirs.reverse();
+            return Some(dirs);
+        }
+        if cost.get(&pos).is_some_and(|&best| moves > best) {
+            continue;
+        }
+        for dir in DIRS {
+            if pos == start && dir == back {
+                continue;
+            }
+            let Some(next) = board.rules.land((pos.0 + dir.0, pos.1 + dir.1)) else {
+                continue;
+            };
+            let arrive = moves + 1;
+            let deadly = board.rules.poisoned(&board.food, next)
+                || board.rules.closed(board.tick + arrive as u32, next)
+                || frees.get(&next).is_some_and(|&wait| arrive <= wait);
+            if deadly || cost.get(&next).is_some_and(|&best| arrive >= best) {
+                continue;
+            }
+            cost.insert(next, arrive);
+            from.insert(next, (pos, dir));
+            let guess = arrive + estimate(board, next, goal);
+            open.push((Reverse(guess), arrive, next));
+        }
+    }
+    None
+}
+
+/// The first heading of the shortest way to `goal`, if there is one.
+pub fn next(board: &Board, me: usize, goal: (i32, i32)) -> Option<(i32, i32)> {
+    plan(board, me, goal)?.first().copied()
+}
+
+// A lower bound on the moves from `a` to `b`: the grid distance, taking the
+// short way round where the board wraps. Portals can beat any distance, so
+// with them on there is no estimate and the search runs as Dijkstra's.
+fn estimate(board: &Board, a: (i32, i32), b: (i32, i32)) -> usize {
+    if board.rules.mutators.portals {
+        return 0;
+    }
+    board.rules.distance(a, b) as usize
+}
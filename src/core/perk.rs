@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+/// Points per level. Roguelike runs offer a perk at every new level.
+pub const LEVEL_POINTS: i32 = 5;
+
+// how much faster a roguelike run gets per level, before perks
+const RAMP_PER_LEVEL: f64 = 0.1;
+
+/// Upgrades picked between the levels of a roguelike run. Each one stacks
+/// with earlier picks for the rest of the run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Perk {
+    /// Halves how much faster each level gets.
+    Ramp,
+    /// Eats food one cell further away.
+    Reach,
+    /// Survives one crash per level.
+    Shield,
+}
+
+/// Every perk, in the order they are offered.
+pub const PERKS: [Perk; 3] = [Perk::Ramp, Perk::Reach, Perk::Shield];
+
+impl Perk {
+    pub fn name(self) -> &'static str {
+        match self {
+            Perk::Ramp => "ramp",
+            Perk::Reach => "reach",
+            Perk::Shield => "shield",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Perk> {
+        PERKS.into_iter().find(|p| p.name() == name)
+    }
+
+    /// Short description for the choice screen.
+    pub fn label(self) -> &'static str {
+        match self {
+            Perk::Ramp => "Slower speed-up",
+            Perk::Reach => "+1 pickup radius",
+            Perk::Shield => "Shield each level",
+        }
+    }
+}
+
+/// The perks a snake has picked so far, by count.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Perks {
+    pub ramp: u32,
+    pub reach: u32,
+    pub shield: u32,
+}
+
+impl Perks {
+    pub fn add(&mut self, perk: Perk) {
+        match perk {
+            Perk::Ramp => self.ramp += 1,
+            Perk::Reach => self.reach += 1,
+            Perk::Shield => self.shield += 1,
+        }
+    }
+
+    pub fn count(&self, perk: Perk) -> u32 {
+        match perk {
+            Perk::Ramp => self.ramp,
+            Perk::Reach => self.reach,
+            Perk::Shield => self.shield,
+        }
+    }
+
+    /// Ticks per tick of the game clock at `level` of a roguelike run.
+    pub fn speed(&self, level: i32) -> f64 {
+        let ramp = RAMP_PER_LEVEL / f64::from(1u32 << self.ramp.min(16));
+        1.0 + f64::from(level) * ramp
+    }
+}
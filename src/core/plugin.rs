@@ -0,0 +1,111 @@
+use crate::core::board::{Board, Event};
+
+/// Extra rules layered on a run without touching `Board::advance`. Hooks
+/// see the board after the fact and answer with events of their own, which
+/// the game applies and logs like any other, so replays stay exact.
+pub trait Plugin {
+    /// What the plugin is called, as `enable_plugin` takes it.
+    fn name(&self) -> &'static str;
+
+    /// After every tick.
+    fn on_tick(&mut self, _board: &Board) -> Vec<Event> {
+        Vec::new()
+    }
+
+    /// After a tick in which `snake` ate.
+    fn on_food_eaten(&mut self, _board: &Board, _snake: usize) -> Vec<Event> {
+        Vec::new()
+    }
+
+    /// After the tick that killed `snake`, which ends the run.
+    fn on_death(&mut self, _board: &Board, _snake: usize) {}
+
+    /// Where food picked at `pos` should appear instead.
+    fn modify_spawn(&mut self, _board: &Board, pos: (i32, i32)) -> (i32, i32) {
+        pos
+    }
+
+    /// How many ticks to run per tick of the game clock.
+    fn speed(&self, _board: &Board) -> f64 {
+        1.0
+    }
+}
+
+/// The plugins a game runs, called in the order they were registered.
+#[derive(Default)]
+pub struct Plugins {
+    plugins: Vec<Box<dyn Plugin>>,
+}
+
+impl Plugins {
+    pub fn register(&mut self, plugin: Box<dyn Plugin>) {
+        self.plugins.push(plugin);
+    }
+
+    /// Whether a plugin called `name` is registered.
+    pub fn has(&self, name: &str) -> bool {
+        self.plugins.iter().any(|p| p.name() == name)
+    }
+
+    pub fn clear(&mut self) {
+        self.plugins.clear();
+    }
+
+    /// Runs the hooks for the tick that took `before` to `board` and
+    /// returns the events they asked for.
+    pub fn ticked(&mut self, before: &Board, board: &Board) -> Vec<Event> {
+        let mut events = Vec::new();
+        for plugin in &mut self.plugins {
+            for i in 0..board.snakes.len() {
+                if board.ate(before, i) {
+                    events.extend(plugin.on_food_eaten(board, i));
+                }
+                if board.died(before, i) {
+                    plugin.on_death(board, i);
+                }
+            }
+            events.extend(plugin.on_tick(board));
+        }
+        events
+    }
+
+    pub fn spawn(&mut self, board: &Board, pos: (i32, i32)) -> (i32, i32) {
+        self.plugins
+            .iter_mut()
+            .fold(pos, |pos, plugin| plugin.modify_spawn(board, pos))
+    }
+
+    pub fn speed(&self, board: &Board) -> f64 {
+        self.plugins.iter().map(|p| p.speed(board)).product()
+    }
+}
+
+/// Speeds the game up as the player scores, up to twice the set speed.
+pub struct SpeedRamp;
+
+impl Plugin for SpeedRamp {
+    fn name(&self) -> &'static str {
+        "speed_ramp"
+    }
+
+    fn speed(&self, board: &Board) -> f64 {
+        let score = board.snakes.iter().map(|s| s.score).max().unwrap_or(0);
+        (1.0 + f64::from(score) * 0.05).min(2.0)
+    }
+}
+
+/// Multiplies the points for every food eaten.
+pub struct ScoreMultiplier {
+    pub factor: i32,
+}
+
+impl Plugin for ScoreMultiplier {
+    fn name(&self) -> &'static str {
+        "score_multiplier"
+    }
+
+    fn on_food_eaten(&mut self, board: &Board, snake: usize) -> Vec<Event> {
+        let points = board.snakes[snake].handicap.multiplier as i32 * (self.factor - 1);
+        vec![Event::ScoreAwarded { snake, points }]
+    }
+}
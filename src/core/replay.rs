@@ -0,0 +1,340 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::board::{Board, Event, Rules, Snake};
+use crate::core::perk::Perk;
+
+const FORMAT: &str = "v2";
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 8.0;
+
+/// A finished single-player run: its score, rules and the event log that
+/// produced it, starting from the default solo snake.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Recording {
+    pub score: i32,
+    #[serde(default)]
+    rules: Rules,
+    events: Vec<Event>,
+}
+
+impl Recording {
+    pub fn new(score: i32, rules: Rules, events: Vec<Event>) -> Self {
+        Self {
+            score,
+            rules,
+            events,
+        }
+    }
+
+    /// Compact text form: `v2|score|events|rules`, with events separated by
+    /// commas. Turns are U/D/L/R (prefixed by the snake index when it isn't
+    /// the first), runs of ticks are `t` plus a count, food is `x:y`,
+    /// awarded points are `+` plus the points, perks are `*` plus the
+    /// perk's name and autopilot stretches start with `@on` and end with
+    /// `@off`, all prefixed the same way.
+    pub fn encode(&self) -> String {
+        let mut tokens: Vec<String> = Vec::new();
+        let mut ticks = 0;
+        for event in &self.events {
+            if *event == Event::TickAdvanced {
+                ticks += 1;
+                continue;
+            }
+            if ticks > 0 {
+                tokens.push(format!("t{ticks}"));
+                ticks = 0;
+            }
+            tokens.push(match *event {
+                Event::TurnRequested { snake: 0, dir } => dir_char(dir).to_string(),
+                Event::TurnRequested { snake, dir } => format!("{snake}{}", dir_char(dir)),
+                Event::FoodSpawned { pos: (x, y) } => format!("{x}:{y}"),
+                Event::ScoreAwarded { snake: 0, points } => format!("+{points}"),
+                Event::ScoreAwarded { snake, points } => format!("{snake}+{points}"),
+                Event::PerkGained { snake: 0, perk } => format!("*{}", perk.name()),
+                Event::PerkGained { snake, perk } => format!("{snake}*{}", perk.name()),
+                Event::AutopilotSet { snake: 0, on } => format!("@{}", on_off(on)),
+                Event::AutopilotSet { snake, on } => format!("{snake}@{}", on_off(on)),
+                Event::TickAdvanced => unreachable!(),
+            });
+        }
+        if ticks > 0 {
+            tokens.push(format!("t{ticks}"));
+        }
+        format!(
+            "{FORMAT}|{}|{}|{}",
+            self.score,
+            tokens.join(","),
+            self.rules.encode()
+        )
+    }
+
+    pub fn decode(text: &str) -> Option<Recording> {
+        let mut parts = text.trim().split('|');
+        if parts.next()? != FORMAT {
+            return None;
+        }
+        let score = parts.next()?.parse().ok()?;
+        let mut events = Vec::new();
+        for token in parts.next()?.split(',').filter(|t| !t.is_empty()) {
+            if let Some(ticks) = token.strip_prefix('t') {
+                let ticks: usize = ticks.parse().ok()?;
+                events.extend(std::iter::repeat_n(Event::TickAdvanced, ticks));
+            } else if let Some((x, y)) = token.split_once(':') {
+                let pos = (x.parse().ok()?, y.parse().ok()?);
+                events.push(Event::FoodSpawned { pos });
+            } else if let Some((snake, points)) = token.split_once('+') {
+                events.push(Event::ScoreAwarded {
+                    snake: snake_index(snake)?,
+                    points: points.parse().ok()?,
+                });
+            } else if let Some((snake, perk)) = token.split_once('*') {
+                events.push(Event::PerkGained {
+                    snake: snake_index(snake)?,
+                    perk: Perk::parse(perk)?,
+                });
+            } else if let Some((snake, on)) = token.split_once('@') {
+                events.push(Event::AutopilotSet {
+                    snake: snake_index(snake)?,
+                    on: match on {
+                        "on" => true,
+                        "off" => false,
+                        _ => return None,
+                    },
+                });
+            } else {
+                let dir = token.chars().last()?;
+                let snake = &token[..token.len() - dir.len_utf8()];
+                events.push(Event::TurnRequested {
+                    snake: snake_index(snake)?,
+                    dir: char_dir(dir)?,
+                });
+            }
+        }
+        // replays from before rules were recorded always wrapped
+        let rules = match parts.next() {
+            Some(rules) => Rules::decode(rules)?,
+            None => Rules::default(),
+        };
+        Some(Recording {
+            score,
+            rules,
+            events,
+        })
+    }
+
+    fn ticks(&self) -> u32 {
+        self.events
+            .iter()
+            .filter(|e| **e == Event::TickAdvanced)
+            .count() as u32
+    }
+}
+
+fn on_off(on: bool) -> &'static str {
+    if on {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+// the snake prefix of a token, which is left off for the first snake
+fn snake_index(prefix: &str) -> Option<usize> {
+    if prefix.is_empty() {
+        Some(0)
+    } else {
+        prefix.parse().ok()
+    }
+}
+
+fn dir_char(dir: (i32, i32)) -> char {
+    match dir {
+        (0, -1) => 'U',
+        (0, 1) => 'D',
+        (-1, 0) => 'L',
+        _ => 'R',
+    }
+}
+
+fn char_dir(c: char) -> Option<(i32, i32)> {
+    match c {
+        'U' => Some((0, -1)),
+        'D' => Some((0, 1)),
+        'L' => Some((-1, 0)),
+        'R' => Some((1, 0)),
+        _ => None,
+    }
+}
+
+/// Plays a recording back tick by tick alongside a live game.
+pub struct Ghost {
+    rec: Recording,
+    // index of the next event to apply
+    next: usize,
+    pub board: Board,
+}
+
+impl Ghost {
+    pub fn new(rec: Recording, snake: Snake) -> Self {
+        let mut ghost = Self {
+            board: Board::new(vec![snake], rec.rules),
+            rec,
+            next: 0,
+        };
+        ghost.settle();
+        ghost
+    }
+
+    /// A ghost already advanced to `tick`, or to the end of the recording.
+    pub fn at(rec: Recording, snake: Snake, tick: u32) -> Self {
+        let mut ghost = Ghost::new(rec, snake);
+        while ghost.tick() < tick && ghost.step() {}
+        ghost
+    }
+
+    pub fn tick(&self) -> u32 {
+        self.board.tick
+    }
+
+    pub fn snake(&self) -> &Snake {
+        &self.board.snakes[0]
+    }
+
+    pub fn score(&self) -> i32 {
+        self.snake().score
+    }
+
+    pub fn food(&self) -> &[(i32, i32)] {
+        &self.board.food
+    }
+
+    /// Advances one tick; returns false once the recording has run out.
+    pub fn step(&mut self) -> bool {
+        let Some(event) = self.rec.events.get(self.next) else {
+            return false;
+        };
+        self.board.apply(event);
+        self.next += 1;
+        self.settle();
+        true
+    }
+
+    // Applies everything recorded between one tick and the next.
+    fn settle(&mut self) {
+        while let Some(event) = self.rec.events.get(self.next) {
+            if *event == Event::TickAdvanced {
+                break;
+            }
+            self.board.apply(event);
+            self.next += 1;
+        }
+    }
+}
+
+/// Re-watches a recording with seeking and variable speed.
+pub struct Playback {
+    start: Snake,
+    pub ghost: Ghost,
+    ticks: u32,
+    speed: f64,
+    // fractional moves carried between game ticks
+    carry: f64,
+}
+
+impl Playback {
+    pub fn new(rec: Recording, start: Snake) -> Self {
+        Self {
+            ticks: rec.ticks(),
+            ghost: Ghost::new(rec, start.clone()),
+            start,
+            speed: 1.0,
+            carry: 0.0,
+        }
+    }
+
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+    }
+
+    /// Runs the moves due in one game tick at the current speed.
+    pub fn advance(&mut self) {
+        self.carry += self.speed;
+        while self.carry >= 1.0 {
+            self.carry -= 1.0;
+            self.ghost.step();
+        }
+    }
+
+    pub fn seek(&mut self, tick: u32) {
+        let rec = self.ghost.rec.clone();
+        self.ghost = Ghost::at(rec, self.start.clone(), tick);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_every_event() {
+        let events = vec![
+            Event::FoodSpawned { pos: (3, 4) },
+            Event::TickAdvanced,
+            Event::TickAdvanced,
+            Event::TurnRequested {
+                snake: 0,
+                dir: (0, -1),
+            },
+            Event::TurnRequested {
+                snake: 1,
+                dir: (-1, 0),
+            },
+            Event::TickAdvanced,
+            Event::ScoreAwarded {
+                snake: 0,
+                points: 2,
+            },
+            Event::PerkGained {
+                snake: 0,
+                perk: Perk::Shield,
+            },
+            Event::AutopilotSet { snake: 0, on: true },
+            Event::AutopilotSet {
+                snake: 0,
+                on: false,
+            },
+            Event::TickAdvanced,
+        ];
+        let rules = Rules::decode("walls+poison").unwrap();
+        let rec = Recording::new(7, rules, events.clone());
+        let text = rec.encode();
+        let back = Recording::decode(&text).unwrap();
+        assert_eq!(back.score, 7);
+        assert!(back.rules == rules);
+        assert_eq!(back.events, events);
+        assert_eq!(back.encode(), text);
+    }
+
+    #[test]
+    fn reads_replays_without_rules() {
+        let rec = Recording::decode("v2|1|5:5,t3,U").unwrap();
+        assert!(rec.rules == Rules::default());
+        assert_eq!(rec.events.len(), 5);
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(Recording::decode("v1|1|t3").is_none());
+        assert!(Recording::decode("v2|x|t3").is_none());
+        assert!(Recording::decode("v2|1|Q").is_none());
+        assert!(Recording::decode("v2|1|t3|sideways").is_none());
+    }
+}
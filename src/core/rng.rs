@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// Seeded splitmix64 generator. Everything that needs randomness during play
+/// draws from the game's `Rng`, so two peers given the same seed simulate the
+/// same food positions.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `0..n`.
+    pub fn below(&mut self, n: i32) -> i32 {
+        (self.next_u64() % n as u64) as i32
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+
+use crate::core::board::{self, Board, Event, Rules};
+use crate::core::replay::Recording;
+use crate::core::rng::Rng;
+use crate::{
+    highscores, leaderboard, random_seed, schema, settings, solo_snake, storage, with_game,
+};
+
+const KEY: &str = "snake_game.correspondence";
+pub const MOVES_PER_DAY: usize = 20;
+
+/// A slow match between two players who each run their own snake on the
+/// same seed, a few moves a day. It lives on the leaderboard server, where
+/// each player only ever writes their own list of moves.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Match {
+    pub seed: u64,
+    pub rules: Rules,
+    pub moves: [Vec<Move>; 2],
+}
+
+/// One tick of a correspondence run, heading `dir`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Move {
+    pub dir: (i32, i32),
+    /// `YYYY-MM-DD` in UTC, for the daily limit.
+    pub day: String,
+}
+
+/// Which side of a match this browser plays, and how much of the
+/// opponent's run it has already shown.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct Seat {
+    slot: usize,
+    seen: u32,
+}
+
+/// A match being played from this browser.
+pub struct Correspondence {
+    pub id: String,
+    pub game: Match,
+    pub slot: usize,
+}
+
+impl Match {
+    /// Whether the rules are in range and every move is a heading, as a
+    /// match fetched from the server must be before it is played out.
+    pub fn valid(&self) -> bool {
+        self.rules.valid()
+            && self
+                .moves
+                .iter()
+                .flatten()
+                .all(|m| board::is_heading(m.dir))
+    }
+
+    /// Plays `slot`'s moves out from the seed, the same way a live game
+    /// ticks.
+    pub fn play_out(&self, slot: usize) -> (Board, Rng, Vec<Event>) {
+        let mut rng = Rng::new(self.seed);
+        let mut board = Board::new(vec![solo_snake()], self.rules);
+        let mut log = Vec::new();
+        let mut dispatch = |board: &mut Board, event: Event| {
+            board.apply(&event);
+            log.push(event);
+        };
+        while let Some(food) = board.spawn_food(&mut rng) {
+            dispatch(&mut board, food);
+        }
+        for m in &self.moves[slot] {
+            if board.over {
+                break;
+            }
+            dispatch(
+                &mut board,
+                Event::TurnRequested {
+                    snake: 0,
+                    dir: m.dir,
+                },
+            );
+            dispatch(&mut board, Event::TickAdvanced);
+            while let Some(food) = board.spawn_food(&mut rng) {
+                dispatch(&mut board, food);
+            }
+        }
+        (board, rng, log)
+    }
+}
+
+impl Correspondence {
+    pub fn moves_left(&self) -> usize {
+        let today = highscores::today();
+        let played = self.game.moves[self.slot]
+            .iter()
+            .filter(|m| m.day == today)
+            .count();
+        MOVES_PER_DAY.saturating_sub(played)
+    }
+
+    /// Records a move and sends this side's moves to the server.
+    pub fn play(&mut self, dir: (i32, i32)) {
+        self.game.moves[self.slot].push(Move {
+            dir,
+            day: highscores::today(),
+        });
+        let path = format!("/correspondence/{}/{}", self.id, self.slot);
+        let body = schema::to_json(&self.game.moves[self.slot]).unwrap_or_default();
+        spawn_local(async move {
+            let _ = leaderboard::post(&path, &body).await;
+        });
+    }
+
+    pub fn rival(&self) -> usize {
+        1 - self.slot
+    }
+
+    /// The opponent's run so far, and the tick this browser last saw it
+    /// reach. Marks all of it as seen.
+    pub fn catch_up(&self) -> (Recording, u32) {
+        let (board, _, log) = self.game.play_out(self.rival());
+        let score = board.snakes[0].score;
+        let mut seats = seats();
+        let seen = seats.get(&self.id).map_or(0, |s| s.seen);
+        seats.insert(
+            self.id.clone(),
+            Seat {
+                slot: self.slot,
+                seen: board.tick,
+            },
+        );
+        storage::store(KEY, &seats);
+        (Recording::new(score, self.game.rules, log), seen)
+    }
+}
+
+fn seats() -> HashMap<String, Seat> {
+    storage::load(KEY).unwrap_or_default()
+}
+
+fn valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Creates a correspondence match on the leaderboard server and starts
+/// playing it. Resolves to the match id for the opponent's
+/// `correspondence_open`.
+#[wasm_bindgen]
+pub async fn correspondence_new() -> Result<String, JsValue> {
+    let id = format!("{:x}", random_seed());
+    let game = Match {
+        seed: random_seed(),
+        rules: settings::rules(),
+        moves: [Vec::new(), Vec::new()],
+    };
+    let body = schema::to_json(&game).unwrap_or_default();
+    leaderboard::post(&format!("/correspondence/{id}"), &body).await?;
+    let mut seats = seats();
+    seats.insert(id.clone(), Seat { slot: 0, seen: 0 });
+    storage::store(KEY, &seats);
+    with_game(|g| {
+        g.start_correspondence(Correspondence {
+            id: id.clone(),
+            game,
+            slot: 0,
+        })
+    });
+    Ok(id)
+}
+
+/// Fetches a correspondence match and resumes it, first replaying whatever
+/// the opponent played since this browser last looked. A browser opening a
+/// match it didn't create takes the second seat.
+#[wasm_bindgen]
+pub async fn correspondence_open(id: String) -> Result<(), JsValue> {
+    if !valid_id(&id) {
+        return Err(JsValue::from_str("invalid match id"));
+    }
+    let text = leaderboard::get(&format!("/correspondence/{id}")).await?;
+    let game = schema::from_json::<Match>(&text)
+        .filter(Match::valid)
+        .ok_or("invalid match")?;
+    let slot = match seats().get(&id) {
+        None => 1,
+        Some(seat) if seat.slot <= 1 => seat.slot,
+        Some(_) => return Err(JsValue::from_str("invalid seat")),
+    };
+    with_game(|g| g.start_correspondence(Correspondence { id, game, slot }));
+    Ok(())
+}
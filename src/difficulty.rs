@@ -0,0 +1,86 @@
+use wasm_bindgen::prelude::*;
+
+use crate::core::board::WrapConfig;
+use crate::settings::{self, Settings};
+
+/// Bundles of settings for players who would rather not tune each one.
+/// Picking one writes its values into the settings, where they can still
+/// be changed one by one afterwards.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Insane,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Insane,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Insane => "insane",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.name() == name)
+    }
+
+    // (tick_ms, wrap, speed_ramp, food_count, obstacles)
+    fn values(self) -> (u32, bool, bool, u32, u32) {
+        match self {
+            Difficulty::Easy => (150, true, false, 3, 0),
+            Difficulty::Normal => (100, true, false, 1, 0),
+            Difficulty::Hard => (80, false, true, 1, 5),
+            Difficulty::Insane => (50, false, true, 1, 10),
+        }
+    }
+
+    pub fn apply(self, s: &mut Settings) {
+        let (tick_ms, wrap, speed_ramp, food_count, obstacles) = self.values();
+        s.tick_ms = tick_ms;
+        s.wrap = WrapConfig::both(wrap);
+        s.speed_ramp = speed_ramp;
+        s.food_count = food_count;
+        s.obstacles = obstacles;
+    }
+
+    /// The preset `s` matches, if it hasn't been changed since.
+    pub fn of(s: &Settings) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| {
+            let (tick_ms, wrap, speed_ramp, food_count, obstacles) = d.values();
+            s.tick_ms == tick_ms
+                && s.wrap == WrapConfig::both(wrap)
+                && s.speed_ramp == speed_ramp
+                && s.food_count == food_count
+                && s.obstacles == obstacles
+        })
+    }
+}
+
+/// Sets the speed, wrapping, speed ramp, food count and obstacles from a
+/// preset: `"easy"`, `"normal"`, `"hard"` or `"insane"`. The speed applies
+/// at once, the rest from the next game.
+#[wasm_bindgen]
+pub fn set_difficulty(name: &str) -> Result<(), JsValue> {
+    let difficulty =
+        Difficulty::parse(name).ok_or_else(|| JsValue::from_str("unknown difficulty"))?;
+    settings::update(|s| difficulty.apply(s));
+    Ok(())
+}
+
+/// The preset the settings match, or nothing once they've been changed
+/// away from every one.
+#[wasm_bindgen]
+pub fn get_difficulty() -> Option<String> {
+    settings::with(|s| Difficulty::of(s).map(|d| d.name().to_string()))
+}
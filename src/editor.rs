@@ -0,0 +1,213 @@
+use wasm_bindgen::prelude::*;
+use web_sys::CanvasRenderingContext2d;
+
+use crate::core::board::{Board, Mutators, Rules, Snake, WrapConfig};
+use crate::theme::Theme;
+
+const FIELDS: [&str; 3] = ["Background", "Text", "Grid"];
+const CHANNELS: [&str; 4] = ["R", "G", "B", "A"];
+// a slider for each channel of each colour, then one for the fade
+const ROWS: usize = FIELDS.len() * CHANNELS.len() + 1;
+const STEP: u8 = 8;
+const TOP: f64 = 36.0;
+const LABEL_W: f64 = 96.0;
+
+/// A colour as the editor's sliders see it.
+#[derive(Clone, Copy)]
+struct Rgba([u8; 4]);
+
+impl Rgba {
+    // Any CSS colour, read back through the canvas, which reports what it
+    // was given as `#rrggbb` or `rgba(r, g, b, a)`.
+    fn parse(ctx: &CanvasRenderingContext2d, css: &str) -> Self {
+        ctx.set_fill_style_str("transparent");
+        ctx.set_fill_style_str(css);
+        let css = ctx.fill_style().as_string().unwrap_or_default();
+        if let Some(hex) = css.strip_prefix('#') {
+            let channel =
+                |i: usize| u8::from_str_radix(hex.get(i..i + 2).unwrap_or("0"), 16).unwrap_or(0);
+            return Rgba([channel(0), channel(2), channel(4), 255]);
+        }
+        let mut parts = css
+            .trim_start_matches("rgba(")
+            .trim_end_matches(')')
+            .split(',')
+            .map(|p| p.trim().parse::<f64>().unwrap_or(0.0));
+        let mut next = || parts.next().unwrap_or(0.0);
+        let (r, g, b, a) = (next(), next(), next(), next());
+        Rgba([r as u8, g as u8, b as u8, (a * 255.0).round() as u8])
+    }
+
+    fn css(self) -> String {
+        let [r, g, b, a] = self.0;
+        if a == 255 {
+            format!("#{r:02x}{g:02x}{b:02x}")
+        } else {
+            format!("rgba({r}, {g}, {b}, {:.2})", f64::from(a) / 255.0)
+        }
+    }
+}
+
+/// The theme editor: a slider for each channel of the background, text and
+/// grid colours and one for the fade, with the theme being edited shown
+/// on a small board beside them.
+pub struct ThemeEditor {
+    /// The edited theme, kept up to date as the sliders move.
+    pub theme: Theme,
+    colours: [Rgba; 3],
+    row: usize,
+}
+
+impl ThemeEditor {
+    /// Starts from `theme`; `ctx` reads its colours.
+    pub fn new(theme: Theme, ctx: &CanvasRenderingContext2d) -> Self {
+        let grid = theme.grid.as_deref().unwrap_or("rgba(128, 128, 128, 0)");
+        let colours = [
+            Rgba::parse(ctx, &theme.background),
+            Rgba::parse(ctx, &theme.ink),
+            Rgba::parse(ctx, grid),
+        ];
+        Self {
+            theme,
+            colours,
+            row: 0,
+        }
+    }
+
+    /// Up and Down pick a slider, Left and Right move it.
+    pub fn key(&mut self, key: &str) {
+        match key {
+            "ArrowUp" => self.row = (self.row + ROWS - 1) % ROWS,
+            "ArrowDown" => self.row = (self.row + 1) % ROWS,
+            "ArrowLeft" => self.nudge(-1.0),
+            "ArrowRight" => self.nudge(1.0),
+            _ => {}
+        }
+    }
+
+    /// Picks the slider at `(x, y)` on a `w`-pixel-wide board and sets it
+    /// from where along it the pointer is.
+    pub fn drag(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        let row = ((y - TOP) / row_height(h)).floor();
+        if row < 0.0 || row >= ROWS as f64 || x < LABEL_W {
+            return;
+        }
+        self.row = row as usize;
+        let (left, width) = track(w, h);
+        self.set((x - left) / width);
+    }
+
+    /// Where the preview goes on a `w` by `h` board, as `(x, y, size)`.
+    pub fn preview(w: f64, h: f64) -> (f64, f64, f64) {
+        let size = (w * 0.4).min(h - TOP - 8.0);
+        (w - size - 8.0, TOP, size)
+    }
+
+    pub fn draw(&self, ctx: &CanvasRenderingContext2d, w: f64, h: f64) -> Result<(), JsValue> {
+        let rh = row_height(h);
+        let (left, width) = track(w, h);
+        ctx.set_fill_style_str(&self.theme.ink);
+        ctx.set_font(&self.theme.font(14.0));
+        ctx.set_text_align("left");
+        ctx.fill_text(
+            "Theme editor \u{b7} Enter save \u{b7} Esc cancel",
+            8.0,
+            20.0,
+        )?;
+        ctx.set_font(&self.theme.font((rh - 4.0).min(12.0)));
+        for row in 0..ROWS {
+            let y = TOP + rh * row as f64;
+            let (label, value, fill) = match row / CHANNELS.len() {
+                field if field < FIELDS.len() => {
+                    let channel = row % CHANNELS.len();
+                    let fill = ["red", "lime", "blue", "gray"][channel];
+                    let value = f64::from(self.colours[field].0[channel]) / 255.0;
+                    (
+                        format!("{} {}", FIELDS[field], CHANNELS[channel]),
+                        value,
+                        fill,
+                    )
+                }
+                _ => ("Fade".to_string(), self.theme.fade, "gray"),
+            };
+            ctx.set_fill_style_str(&self.theme.ink);
+            ctx.fill_text(&label, 8.0, y + rh - 4.0)?;
+            ctx.set_fill_style_str("rgba(128, 128, 128, 0.5)");
+            ctx.fill_rect(left, y + 2.0, width, rh - 4.0);
+            ctx.set_fill_style_str(fill);
+            ctx.fill_rect(left, y + 2.0, width * value, rh - 4.0);
+            if row == self.row {
+                ctx.set_stroke_style_str("gold");
+                ctx.stroke_rect(left, y + 2.0, width, rh - 4.0);
+            }
+        }
+        // a swatch of each colour as it stands, under the sliders
+        let y = TOP + rh * ROWS as f64 + 4.0;
+        for (i, colour) in self.colours.iter().enumerate() {
+            ctx.set_fill_style_str(&colour.css());
+            ctx.fill_rect(left + (rh + 4.0) * i as f64, y, rh, rh);
+            ctx.set_stroke_style_str("gray");
+            ctx.stroke_rect(left + (rh + 4.0) * i as f64, y, rh, rh);
+        }
+        Ok(())
+    }
+
+    fn nudge(&mut self, by: f64) {
+        if self.row == ROWS - 1 {
+            self.set(self.theme.fade + by * 0.05);
+        } else {
+            let (field, channel) = (self.row / CHANNELS.len(), self.row % CHANNELS.len());
+            let value = f64::from(self.colours[field].0[channel]) + by * f64::from(STEP);
+            self.set(value / 255.0);
+        }
+    }
+
+    // Sets the selected slider to `at`, from 0 to 1.
+    fn set(&mut self, at: f64) {
+        let at = at.clamp(0.0, 1.0);
+        if self.row == ROWS - 1 {
+            self.theme.fade = (at * 20.0).round() / 20.0;
+            return;
+        }
+        let (field, channel) = (self.row / CHANNELS.len(), self.row % CHANNELS.len());
+        self.colours[field].0[channel] = (at * 255.0).round() as u8;
+        let css = self.colours[field].css();
+        match field {
+            0 => self.theme.background = css,
+            1 => self.theme.ink = css,
+            _ => self.theme.grid = (self.colours[2].0[3] > 0).then_some(css),
+        }
+    }
+}
+
+/// A small made-up board for the preview, with a bit of everything the
+/// theme touches on it.
+pub fn sample() -> Board {
+    let mut me = Snake::new((10, 9), (1, 0));
+    me.body
+        .extend([(9, 9), (8, 9), (8, 10), (8, 11), (9, 11), (10, 11)]);
+    let mut rival = Snake::new((12, 13), (-1, 0));
+    rival.body.extend([(13, 13), (14, 13)]);
+    let rules = Rules {
+        wrap: WrapConfig::both(false),
+        mutators: Mutators {
+            poison: true,
+            tiny: true,
+            ..Mutators::default()
+        },
+        ..Rules::default()
+    };
+    let mut board = Board::new(vec![me, rival], rules);
+    board.food = vec![(13, 9)];
+    board
+}
+
+fn row_height(h: f64) -> f64 {
+    ((h - TOP - 40.0) / (ROWS + 1) as f64).min(20.0)
+}
+
+// the sliders' left edge and width
+fn track(w: f64, h: f64) -> (f64, f64) {
+    let (preview_x, _, _) = ThemeEditor::preview(w, h);
+    (LABEL_W, (preview_x - LABEL_W - 12.0).max(20.0))
+}
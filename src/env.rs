@@ -0,0 +1,52 @@
+use std::cell::RefCell;
+
+use wasm_bindgen::prelude::*;
+
+use crate::core::board::Rules;
+use crate::core::env::{Env, ACTIONS};
+
+thread_local! {
+    static ENV: RefCell<Option<Env>> = const { RefCell::new(None) };
+}
+
+/// Starts a new training game on `seed`, apart from whatever the page is
+/// showing, and returns its first observation as JSON. `rules` is written
+/// like `"walls+tiny"`, as for the visual report, and defaults to wrapping
+/// with no mutators.
+#[wasm_bindgen]
+pub fn env_reset(seed: u32, rules: Option<String>) -> Result<String, JsValue> {
+    let rules = match rules {
+        Some(text) => Rules::decode(&text).ok_or("invalid rules")?,
+        None => Rules::default(),
+    };
+    let env = Env::new(u64::from(seed), rules);
+    let json = to_json(&env.observe());
+    ENV.with(|e| e.replace(Some(env)));
+    json
+}
+
+/// Takes `action` (0 up, 1 right, 2 down, 3 left) and runs a tick. Returns
+/// `{observation, reward, done}` as JSON.
+#[wasm_bindgen]
+pub fn env_step(action: usize) -> Result<String, JsValue> {
+    if action >= ACTIONS.len() {
+        return Err(JsValue::from_str("the action must be 0 to 3"));
+    }
+    ENV.with(|e| match e.borrow_mut().as_mut() {
+        Some(env) => to_json(&env.step(action)),
+        None => Err(JsValue::from_str("call env_reset first")),
+    })
+}
+
+/// The training game's current observation as JSON.
+#[wasm_bindgen]
+pub fn env_state() -> Result<String, JsValue> {
+    ENV.with(|e| match e.borrow().as_ref() {
+        Some(env) => to_json(&env.observe()),
+        None => Err(JsValue::from_str("call env_reset first")),
+    })
+}
+
+fn to_json(value: &impl serde::Serialize) -> Result<String, JsValue> {
+    serde_json::to_string(value).map_err(|e| JsValue::from_str(&e.to_string()))
+}
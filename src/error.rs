@@ -0,0 +1,108 @@
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+use crate::theme;
+
+// the size of a canvas made to show the error on, when the page had none
+const FALLBACK_SIZE: (u32, u32) = (480, 320);
+const MARGIN: f64 = 24.0;
+const LINE: f64 = 20.0;
+
+/// What can stop the game from setting up or carrying on.
+#[derive(Debug)]
+pub enum Error {
+    /// Something the game needs from the page isn't there.
+    Missing(&'static str),
+    /// A browser call failed.
+    Browser(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Missing(what) => write!(f, "the page has no {what}"),
+            Error::Browser(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<JsValue> for Error {
+    fn from(value: JsValue) -> Self {
+        let message = value
+            .dyn_ref::<js_sys::Error>()
+            .map(|e| String::from(e.message()))
+            .or_else(|| value.as_string())
+            .unwrap_or_else(|| format!("{value:?}"));
+        Error::Browser(message)
+    }
+}
+
+impl From<Error> for JsValue {
+    fn from(error: Error) -> Self {
+        js_sys::Error::new(&error.to_string()).into()
+    }
+}
+
+/// Says what went wrong on the game's canvas, or on a new one if the page
+/// has none to draw on, so a broken page doesn't just stay blank.
+pub fn show(error: &Error) {
+    web_sys::console::error_1(&JsValue::from_str(&error.to_string()));
+    let _ = draw(error);
+}
+
+fn draw(error: &Error) -> Option<()> {
+    let document = web_sys::window()?.document()?;
+    let canvas = match document.get_element_by_id("game") {
+        Some(element) => element.dyn_into::<HtmlCanvasElement>().ok()?,
+        None => {
+            let canvas: HtmlCanvasElement =
+                document.create_element("canvas").ok()?.dyn_into().ok()?;
+            canvas.set_width(FALLBACK_SIZE.0);
+            canvas.set_height(FALLBACK_SIZE.1);
+            document.body()?.append_child(&canvas).ok()?;
+            canvas
+        }
+    };
+    let ctx: CanvasRenderingContext2d = canvas.get_context("2d").ok()??.dyn_into().ok()?;
+    let theme = theme::current();
+    let (w, h) = (f64::from(canvas.width()), f64::from(canvas.height()));
+    ctx.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0).ok()?;
+    ctx.set_fill_style_str(&theme.background);
+    ctx.fill_rect(0.0, 0.0, w, h);
+    ctx.set_fill_style_str(&theme.ink);
+    ctx.set_text_align("left");
+    ctx.set_font(&theme.font(18.0));
+    let mut y = MARGIN + LINE;
+    ctx.fill_text("Something went wrong", MARGIN, y).ok()?;
+    ctx.set_font(&theme.font(13.0));
+    for line in wrap(&ctx, &error.to_string(), w - 2.0 * MARGIN) {
+        y += LINE;
+        ctx.fill_text(&line, MARGIN, y).ok()?;
+    }
+    ctx.fill_text("Reload the page to try again.", MARGIN, y + 2.0 * LINE)
+        .ok()
+}
+
+// Breaks `text` at spaces into lines no wider than `width`.
+fn wrap(ctx: &CanvasRenderingContext2d, text: &str, width: f64) -> Vec<String> {
+    let fits = |line: &str| ctx.measure_text(line).is_ok_and(|m| m.width() <= width);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let longer = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{line} {word}")
+        };
+        if line.is_empty() || fits(&longer) {
+            line = longer;
+        } else {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        }
+    }
+    lines.push(line);
+    lines
+}
@@ -0,0 +1,59 @@
+use js_sys::{Array, Function, Intl, Object, Reflect};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+// scores from here up are shortened, as in "12.4K"
+const COMPACT_FROM: i32 = 10_000;
+
+thread_local! {
+    // (full, compact) `format` functions for the browser's locale
+    static FORMATS: (Option<Function>, Option<Function>) = (formatter(false), formatter(true));
+}
+
+fn formatter(compact: bool) -> Option<Function> {
+    let options = Object::new();
+    if compact {
+        Reflect::set(&options, &"notation".into(), &"compact".into()).ok()?;
+        Reflect::set(&options, &"maximumFractionDigits".into(), &1.into()).ok()?;
+    }
+    let format = Intl::NumberFormat::new(&Array::new(), &options);
+    Reflect::get(&format, &"format".into())
+        .ok()?
+        .dyn_into()
+        .ok()
+}
+
+/// A score as the player's locale writes numbers, with thousands
+/// separators, shortened once it reaches five digits. Everything that
+/// draws a score goes through here.
+pub fn score(n: i32) -> String {
+    FORMATS.with(|(full, compact)| {
+        let format = if n.abs() >= COMPACT_FROM {
+            compact
+        } else {
+            full
+        };
+        format
+            .as_ref()
+            .and_then(|f| f.call1(&JsValue::NULL, &n.into()).ok())
+            .and_then(|s| s.as_string())
+            .unwrap_or_else(|| n.to_string())
+    })
+}
+
+/// The day of `ms` since the epoch, as the player's locale writes dates.
+pub fn date(ms: f64) -> String {
+    let format = Intl::DateTimeFormat::new(&Array::new(), &Object::new());
+    format
+        .format()
+        .call1(&JsValue::NULL, &js_sys::Date::new(&ms.into()))
+        .ok()
+        .and_then(|s| s.as_string())
+        .unwrap_or_default()
+}
+
+/// `score` for the page's own score lists.
+#[wasm_bindgen]
+pub fn format_score(n: i32) -> String {
+    score(n)
+}
@@ -0,0 +1,40 @@
+use std::cell::RefCell;
+
+use wasm_bindgen::prelude::*;
+
+use crate::core::handicap::{Handicap, MAX_EXTRA_LENGTH, MAX_MULTIPLIER, MAX_SLOWDOWN};
+use crate::tournament::MAX_PLAYERS;
+
+thread_local! {
+    static LOBBY: RefCell<[Handicap; MAX_PLAYERS]> =
+        RefCell::new([Handicap::default(); MAX_PLAYERS]);
+}
+
+/// Sets the handicap for a lobby slot: the tournament player at that index,
+/// or in versus play slot 0 for the host and 1 for the guest (chosen by the
+/// host).
+#[wasm_bindgen]
+pub fn set_handicap(
+    slot: usize,
+    slowdown: u32,
+    extra_length: u32,
+    multiplier: u32,
+) -> Result<(), JsValue> {
+    let handicap = Handicap {
+        slowdown,
+        extra_length,
+        multiplier,
+    };
+    if slot >= MAX_PLAYERS || !handicap.valid() {
+        return Err(JsValue::from_str(&format!(
+            "handicap out of range: slot < {MAX_PLAYERS}, slowdown <= {MAX_SLOWDOWN}, \
+             extra length <= {MAX_EXTRA_LENGTH}, multiplier 1..={MAX_MULTIPLIER}"
+        )));
+    }
+    LOBBY.with(|lobby| lobby.borrow_mut()[slot] = handicap);
+    Ok(())
+}
+
+pub fn lobby(slot: usize) -> Handicap {
+    LOBBY.with(|lobby| lobby.borrow().get(slot).copied().unwrap_or_default())
+}
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::core::board::Mutators;
+use crate::core::names;
+use crate::core::rng::Rng;
+use crate::{random_seed, settings, storage};
+
+const KEY: &str = "snake_game.high_scores";
+const MAX_ENTRIES: usize = 10;
+const MAX_INITIALS: usize = 3;
+
+/// Initials recorded with this player's future high scores; `undefined`
+/// records none.
+#[wasm_bindgen]
+pub fn set_initials(initials: Option<String>) {
+    let initials = initials
+        .map(|s| {
+            s.trim()
+                .to_uppercase()
+                .chars()
+                .take(MAX_INITIALS)
+                .collect::<String>()
+        })
+        .filter(|s| !s.is_empty());
+    settings::update(|s| s.initials = initials);
+}
+
+pub fn initials() -> Option<String> {
+    settings::with(|s| s.initials.clone())
+}
+
+/// What this player is called to others: their initials, or else their
+/// guest name.
+pub fn player_name() -> String {
+    initials().unwrap_or_else(guest_name)
+}
+
+/// The made-up name this player goes by until they set initials, such as
+/// "Turbo Cobra 42". It is picked the first time it is needed and kept.
+#[wasm_bindgen]
+pub fn guest_name() -> String {
+    match settings::with(|s| s.guest_name.clone()) {
+        Some(name) => name,
+        None => new_guest_name(),
+    }
+}
+
+/// Swaps the guest name for a fresh one and returns it.
+#[wasm_bindgen]
+pub fn new_guest_name() -> String {
+    let name = names::generate(&mut Rng::new(random_seed()));
+    settings::update(|s| s.guest_name = Some(name.clone()));
+    name
+}
+
+/// Today's date as `YYYY-MM-DD` in UTC.
+pub fn today() -> String {
+    String::from(js_sys::Date::new_0().to_iso_string())
+        .chars()
+        .take(10)
+        .collect()
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub score: i32,
+    pub mode: String,
+    /// `YYYY-MM-DD` in UTC.
+    pub date: String,
+    pub initials: Option<String>,
+    /// The run mutators that were on.
+    #[serde(default)]
+    pub mutators: Vec<String>,
+}
+
+/// The top ten scores on this device, highest first, kept in localStorage.
+pub struct HighScores {
+    entries: Vec<Entry>,
+}
+
+impl HighScores {
+    pub fn load() -> Self {
+        Self {
+            entries: storage::load(KEY).unwrap_or_default(),
+        }
+    }
+
+    /// Adds a finished run, returning its rank if it made the table.
+    pub fn submit(&mut self, score: i32, mode: &str, mutators: &Mutators) -> Option<usize> {
+        if score <= 0 {
+            return None;
+        }
+        let at = self.entries.partition_point(|e| e.score >= score);
+        if at >= MAX_ENTRIES {
+            return None;
+        }
+        self.entries.insert(
+            at,
+            Entry {
+                score,
+                mode: mode.to_string(),
+                date: today(),
+                initials: initials(),
+                mutators: mutators.names().into_iter().map(String::from).collect(),
+            },
+        );
+        self.entries.truncate(MAX_ENTRIES);
+        storage::store(KEY, &self.entries);
+        Some(at)
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.entries).unwrap_or_default()
+    }
+}
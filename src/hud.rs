@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use web_sys::CanvasRenderingContext2d;
+
+use crate::core::board::Board;
+use crate::core::{HEIGHT, WIDTH};
+use crate::{palette, settings, theme};
+
+// space between the board's edge and the HUD
+const MARGIN: f64 = 4.0;
+const MINIMAP_CELL: f64 = 3.0;
+// width and height of a meter
+const METER: (f64, f64) = (60.0, 8.0);
+
+/// Something the HUD can show.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Widget {
+    Score,
+    Speed,
+    Timer,
+    Combo,
+    /// The boost's stamina meter, while it isn't full.
+    Stamina,
+    Minimap,
+    /// Shields, the autopilot and the boost, while any is on.
+    Effects,
+    /// The live stats panel, shown while it is toggled on.
+    Stats,
+    /// The debug overlay, shown while it is toggled on.
+    Debug,
+}
+
+/// A corner or edge of the board that widgets stack from.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    const ALL: [Anchor; 6] = [
+        Anchor::TopLeft,
+        Anchor::Top,
+        Anchor::TopRight,
+        Anchor::BottomLeft,
+        Anchor::Bottom,
+        Anchor::BottomRight,
+    ];
+
+    fn top(self) -> bool {
+        matches!(self, Anchor::TopLeft | Anchor::Top | Anchor::TopRight)
+    }
+
+    // where text lines up across the board, and how
+    fn x(self, w: f64) -> (f64, &'static str) {
+        match self {
+            Anchor::TopLeft | Anchor::BottomLeft => (MARGIN, "left"),
+            Anchor::Top | Anchor::Bottom => (w / 2.0, "center"),
+            Anchor::TopRight | Anchor::BottomRight => (w - MARGIN, "right"),
+        }
+    }
+}
+
+/// Where one widget goes. Widgets sharing an anchor stack away from the
+/// edge, highest priority nearest it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Placement {
+    pub widget: Widget,
+    pub anchor: Anchor,
+    #[serde(default)]
+    pub priority: i32,
+}
+
+/// Which widgets the HUD shows and where. A widget left out isn't drawn.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct HudLayout(pub Vec<Placement>);
+
+impl Default for HudLayout {
+    fn default() -> Self {
+        let place = |widget, anchor, priority| Placement {
+            widget,
+            anchor,
+            priority,
+        };
+        Self(vec![
+            place(Widget::Score, Anchor::TopLeft, 10),
+            place(Widget::Stamina, Anchor::BottomLeft, 10),
+            place(Widget::Debug, Anchor::TopLeft, 0),
+            place(Widget::Speed, Anchor::TopRight, 10),
+            place(Widget::Effects, Anchor::TopRight, 5),
+            place(Widget::Stats, Anchor::TopRight, 0),
+        ])
+    }
+}
+
+/// What a widget draws this frame.
+pub enum Content<'a> {
+    /// Lines of text, `small` for the monospace overlays.
+    Text { lines: Vec<String>, small: bool },
+    /// The board in miniature, coloured for the player of snake `local`.
+    Minimap { board: &'a Board, local: usize },
+    /// A bar `fill` of the way full, from 0 to 1.
+    Meter { fill: f64 },
+}
+
+impl Content<'_> {
+    pub fn line(text: String) -> Self {
+        Content::Text {
+            lines: vec![text],
+            small: false,
+        }
+    }
+
+    fn height(&self) -> f64 {
+        match self {
+            Content::Text { lines, small } => line_height(*small) * lines.len() as f64,
+            Content::Minimap { .. } => MINIMAP_CELL * HEIGHT as f64 + MARGIN,
+            Content::Meter { .. } => METER.1 + MARGIN,
+        }
+    }
+}
+
+fn line_height(small: bool) -> f64 {
+    if small {
+        14.0
+    } else {
+        18.0
+    }
+}
+
+/// Draws the saved layout over a board `w` by `h` pixels. `content` says
+/// what each widget shows, or `None` to leave it out this frame.
+pub fn draw<'a>(
+    ctx: &CanvasRenderingContext2d,
+    w: f64,
+    h: f64,
+    mut content: impl FnMut(Widget) -> Option<Content<'a>>,
+) -> Result<(), JsValue> {
+    let layout = settings::with(|s| s.hud.clone());
+    let theme = theme::current();
+    for anchor in Anchor::ALL {
+        let mut placed: Vec<_> = layout.0.iter().filter(|p| p.anchor == anchor).collect();
+        placed.sort_by_key(|p| -p.priority);
+        let (x, align) = anchor.x(w);
+        // distance stacked so far from the anchor's edge
+        let mut used = 0.0;
+        for placement in placed {
+            let Some(content) = content(placement.widget) else {
+                continue;
+            };
+            let height = content.height();
+            let top = if anchor.top() {
+                used
+            } else {
+                h - used - height
+            };
+            used += height;
+            match content {
+                Content::Text { lines, small } => {
+                    let lh = line_height(small);
+                    ctx.set_fill_style_str(&theme.ink);
+                    ctx.set_font(&if small {
+                        theme.mono(12.0)
+                    } else {
+                        theme.font(14.0)
+                    });
+                    ctx.set_text_align(align);
+                    for (i, line) in lines.iter().enumerate() {
+                        ctx.fill_text(line, x, top + lh * (i + 1) as f64 - 2.0)?;
+                    }
+                }
+                Content::Minimap { board, local } => {
+                    let (mw, mh) = (MINIMAP_CELL * WIDTH as f64, MINIMAP_CELL * HEIGHT as f64);
+                    let left = left(x, align, mw);
+                    ctx.set_fill_style_str("rgba(128, 128, 128, 0.5)");
+                    ctx.fill_rect(left, top + MARGIN / 2.0, mw, mh);
+                    let palette = palette::current();
+                    for e in board.entities() {
+                        ctx.set_fill_style_str(palette.of(e.kind, local));
+                        ctx.fill_rect(
+                            left + f64::from(e.pos.0) * MINIMAP_CELL,
+                            top + MARGIN / 2.0 + f64::from(e.pos.1) * MINIMAP_CELL,
+                            MINIMAP_CELL,
+                            MINIMAP_CELL,
+                        );
+                    }
+                }
+                Content::Meter { fill } => {
+                    let (mw, mh) = METER;
+                    let (left, top) = (left(x, align, mw), top + MARGIN / 2.0);
+                    ctx.set_stroke_style_str(&theme.ink);
+                    ctx.stroke_rect(left, top, mw, mh);
+                    ctx.set_fill_style_str(&theme.ink);
+                    ctx.fill_rect(left, top, mw * fill.clamp(0.0, 1.0), mh);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// The left edge of something `width` wide lined up at `x` by `align`.
+fn left(x: f64, align: &str, width: f64) -> f64 {
+    match align {
+        "left" => x,
+        "center" => x - width / 2.0,
+        _ => x - width,
+    }
+}
+
+/// Replaces the HUD layout with a JSON array of placements, such as
+/// `[{"widget": "score", "anchor": "top_left", "priority": 1}]`.
+#[wasm_bindgen]
+pub fn set_hud_layout(json: &str) -> Result<(), JsValue> {
+    let layout: HudLayout = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    settings::update(|s| s.hud = layout);
+    Ok(())
+}
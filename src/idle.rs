@@ -0,0 +1,17 @@
+use wasm_bindgen::prelude::*;
+
+use crate::settings;
+
+/// Pauses a game after `secs` seconds without input; 0 (the default) turns
+/// idle detection off.
+#[wasm_bindgen]
+pub fn set_idle_timeout(secs: u32) {
+    settings::update(|s| s.idle_timeout = secs);
+}
+
+pub fn timeout_ms() -> Option<f64> {
+    match settings::with(|s| s.idle_timeout) {
+        0 => None,
+        secs => Some(secs as f64 * 1000.0),
+    }
+}
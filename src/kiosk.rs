@@ -0,0 +1,80 @@
+use std::cell::Cell;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlElement, KeyboardEvent};
+
+use crate::format;
+use crate::highscores::Entry;
+
+// how long a finished game stays on screen before the demo takes over
+pub const AUTO_RESET_MS: f64 = 10_000.0;
+const TICKER_MS: f64 = 2_000.0;
+// page elements hidden while the kiosk is locked
+const LOCKED: &str = "a, [data-kiosk-lock]";
+
+thread_local! {
+    static KIOSK: Cell<bool> = const { Cell::new(false) };
+    static UNLOCKED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Kiosk mode for installations: idle games hand over to the attract-mode
+/// demo, finished games reset on their own, and links and settings stay
+/// hidden until Ctrl+Shift+K is pressed.
+#[wasm_bindgen]
+pub fn set_kiosk(on: bool) {
+    KIOSK.set(on);
+    UNLOCKED.set(false);
+    apply_lock();
+}
+
+pub fn enabled() -> bool {
+    KIOSK.get()
+}
+
+/// Whether the kiosk is on and hasn't been unlocked with Ctrl+Shift+K.
+pub fn locked() -> bool {
+    enabled() && !UNLOCKED.get()
+}
+
+pub fn is_unlock_combo(event: &KeyboardEvent) -> bool {
+    event.ctrl_key() && event.shift_key() && event.key().eq_ignore_ascii_case("k")
+}
+
+pub fn toggle_unlock() {
+    if enabled() {
+        UNLOCKED.set(!UNLOCKED.get());
+        apply_lock();
+    }
+}
+
+/// The high-score entry to show at time `now`, cycling through the table.
+pub fn ticker(entries: &[Entry], now: f64) -> Option<String> {
+    if entries.is_empty() {
+        return None;
+    }
+    let rank = (now / TICKER_MS) as usize % entries.len();
+    let entry = &entries[rank];
+    Some(format!(
+        "Top {} \u{b7} #{} {} \u{2014} {}",
+        entries.len(),
+        rank + 1,
+        entry.initials.as_deref().unwrap_or("---"),
+        format::score(entry.score)
+    ))
+}
+
+fn apply_lock() {
+    let display = if locked() { "none" } else { "" };
+    let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+        return;
+    };
+    let Ok(nodes) = document.query_selector_all(LOCKED) else {
+        return;
+    };
+    for i in 0..nodes.length() {
+        if let Some(el) = nodes.item(i).and_then(|n| n.dyn_into::<HtmlElement>().ok()) {
+            let _ = el.style().set_property("display", display);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{HtmlCanvasElement, HtmlElement, Window};
+
+use crate::core::{HEIGHT, WIDTH};
+
+const MIN_CELL: f64 = 10.0;
+const MAX_CELL: f64 = 40.0;
+// vertical space kept free for the restart button
+const HUD_RESERVE: f64 = 44.0;
+// matches the canvas border in index.html
+const BORDER: f64 = 2.0;
+
+pub struct Layout {
+    /// Size of a cell in CSS pixels. Everything is drawn in these.
+    pub cell: f64,
+    /// Device pixels to a CSS pixel. The canvas holds this many pixels for
+    /// each CSS pixel it shows, so the board stays sharp on hi-DPI screens.
+    pub scale: f64,
+    pub usable: bool,
+}
+
+/// Pads the page body by the iOS safe-area insets so the HUD and board stay
+/// clear of notches and the home indicator.
+pub fn install_safe_area(body: &HtmlElement) -> Result<(), JsValue> {
+    let style = body.style();
+    style.set_property("box-sizing", "border-box")?;
+    style.set_property(
+        "padding",
+        "env(safe-area-inset-top) env(safe-area-inset-right) \
+         env(safe-area-inset-bottom) env(safe-area-inset-left)",
+    )?;
+    Ok(())
+}
+
+pub fn measure(window: &Window, body: &HtmlElement) -> Result<Layout, JsValue> {
+    let width = window.inner_width()?.as_f64().unwrap_or(0.0);
+    let height = window.inner_height()?.as_f64().unwrap_or(0.0);
+    // fullscreen, the board has the whole screen to itself
+    let fullscreen = window
+        .document()
+        .and_then(|d| d.fullscreen_element())
+        .is_some();
+    let reserve = if fullscreen { 0.0 } else { HUD_RESERVE };
+    let (top, right, bottom, left) = match window.get_computed_style(body)? {
+        Some(style) if !fullscreen => (
+            px(&style.get_property_value("padding-top")?),
+            px(&style.get_property_value("padding-right")?),
+            px(&style.get_property_value("padding-bottom")?),
+            px(&style.get_property_value("padding-left")?),
+        ),
+        _ => (0.0, 0.0, 0.0, 0.0),
+    };
+    let avail_w = width - left - right - 2.0 * BORDER;
+    let avail_h = height - top - bottom - reserve - 2.0 * BORDER;
+    let cell = (avail_w / WIDTH as f64)
+        .min(avail_h / HEIGHT as f64)
+        .min(MAX_CELL)
+        .floor();
+    Ok(Layout {
+        cell: cell.max(MIN_CELL),
+        scale: window.device_pixel_ratio().max(1.0),
+        usable: cell >= MIN_CELL,
+    })
+}
+
+/// Sizes the canvas to show the board at `layout.cell` CSS pixels a cell,
+/// backed by `layout.scale` device pixels to each.
+pub fn apply(canvas: &HtmlCanvasElement, layout: &Layout) -> Result<(), JsValue> {
+    let (w, h) = (WIDTH as f64 * layout.cell, HEIGHT as f64 * layout.cell);
+    canvas.set_width((w * layout.scale).round() as u32);
+    canvas.set_height((h * layout.scale).round() as u32);
+    let style = canvas.style();
+    style.set_property("width", &format!("{w}px"))?;
+    style.set_property("height", &format!("{h}px"))?;
+    Ok(())
+}
+
+/// Shows the board fullscreen, with the cells made as large as the screen
+/// allows. Leaving fullscreen, with Escape or `exit_fullscreen()`, puts the
+/// page back as it was.
+#[wasm_bindgen]
+pub fn enter_fullscreen() -> Result<(), JsValue> {
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or("no document")?;
+    document
+        .get_element_by_id("game")
+        .ok_or("no canvas")?
+        .request_fullscreen()
+}
+
+#[wasm_bindgen]
+pub fn exit_fullscreen() {
+    if let Some(document) = web_sys::window().and_then(|w| w.document()) {
+        document.exit_fullscreen();
+    }
+}
+
+/// Enters fullscreen, or leaves it.
+pub fn toggle_fullscreen() {
+    let fullscreen = web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.fullscreen_element())
+        .is_some();
+    if fullscreen {
+        exit_fullscreen();
+    } else {
+        let _ = enter_fullscreen();
+    }
+}
+
+fn px(value: &str) -> f64 {
+    value.trim_end_matches("px").parse().unwrap_or(0.0)
+}
@@ -0,0 +1,180 @@
+use std::cell::RefCell;
+
+use js_sys::{Array, Object, Reflect, Uint8Array};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{CryptoKey, Headers, RequestInit, Response, SubtleCrypto};
+
+use crate::core::board::Mutators;
+use crate::highscores;
+use crate::privacy::{self, Data};
+
+#[derive(Clone)]
+struct Server {
+    url: String,
+    // shared with the server, which checks each submission's signature
+    key: String,
+}
+
+thread_local! {
+    static SERVER: RefCell<Option<Server>> = const { RefCell::new(None) };
+}
+
+/// What gets POSTed for a finished run. The signature is an HMAC-SHA256 of
+/// `score|initials|date|replay_hash` under the shared key, and the replay
+/// itself goes along so the server can re-simulate the run. `name` is the
+/// player's initials or guest name, for display.
+#[derive(Serialize)]
+struct Submission {
+    score: i32,
+    initials: Option<String>,
+    name: String,
+    date: String,
+    mutators: Vec<String>,
+    replay: String,
+    replay_hash: String,
+    signature: String,
+}
+
+/// One row of the global top list as the server returns it.
+#[derive(Serialize, Deserialize)]
+struct Ranked {
+    score: i32,
+    #[serde(default)]
+    initials: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    date: String,
+}
+
+/// Sends finished single-player runs to the leaderboard at `url`, signed
+/// with `key`; `undefined` stops submitting.
+#[wasm_bindgen]
+pub fn set_leaderboard(url: Option<String>, key: String) {
+    SERVER.with(|s| s.replace(url.map(|url| Server { url, key })));
+}
+
+/// Fetches the global top list as a JSON array of
+/// `{score, initials, name, date}` objects.
+#[wasm_bindgen]
+pub async fn get_global_scores() -> Result<String, JsValue> {
+    let text = get("").await?;
+    let list: Vec<Ranked> = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+    Ok(serde_json::to_string(&list).unwrap_or_default())
+}
+
+/// Fetches `path` on the leaderboard server and returns the body text.
+pub async fn get(path: &str) -> Result<String, JsValue> {
+    let server = configured().ok_or("no leaderboard is configured")?;
+    let window = web_sys::window().ok_or("no window")?;
+    let response: Response =
+        JsFuture::from(window.fetch_with_str(&format!("{}{path}", server.url)))
+            .await?
+            .dyn_into()?;
+    checked(response).await
+}
+
+/// POSTs a JSON `body` to `path` on the leaderboard server.
+pub async fn post(path: &str, body: &str) -> Result<(), JsValue> {
+    let server = configured().ok_or("no leaderboard is configured")?;
+    let window = web_sys::window().ok_or("no window")?;
+    let headers = Headers::new()?;
+    headers.set("Content-Type", "application/json")?;
+    let init = RequestInit::new();
+    init.set_method("POST");
+    init.set_headers(&headers);
+    init.set_body(&JsValue::from_str(body));
+    let url = format!("{}{path}", server.url);
+    let response: Response = JsFuture::from(window.fetch_with_str_and_init(&url, &init))
+        .await?
+        .dyn_into()?;
+    checked(response).await.map(drop)
+}
+
+async fn checked(response: Response) -> Result<String, JsValue> {
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!(
+            "leaderboard returned {}",
+            response.status()
+        )));
+    }
+    Ok(JsFuture::from(response.text()?)
+        .await?
+        .as_string()
+        .unwrap_or_default())
+}
+
+/// Submits a run in the background if a leaderboard is configured and the
+/// player allows it.
+pub fn submit(score: i32, mutators: &Mutators, replay: String) {
+    let Some(server) = configured().filter(|_| privacy::allows(Data::Telemetry)) else {
+        return;
+    };
+    let mutators = mutators.names().into_iter().map(String::from).collect();
+    spawn_local(async move {
+        let _ = sign_and_post(server, score, mutators, replay).await;
+    });
+}
+
+fn configured() -> Option<Server> {
+    SERVER.with(|s| s.borrow().clone())
+}
+
+async fn sign_and_post(
+    server: Server,
+    score: i32,
+    mutators: Vec<String>,
+    replay: String,
+) -> Result<(), JsValue> {
+    let window = web_sys::window().ok_or("no window")?;
+    let subtle = window.crypto()?.subtle();
+    let digest =
+        JsFuture::from(subtle.digest_with_str_and_u8_array("SHA-256", replay.as_bytes())?).await?;
+    let replay_hash = hex(&Uint8Array::new(&digest).to_vec());
+    let initials = highscores::initials();
+    let date = highscores::today();
+    let message = format!(
+        "{score}|{}|{date}|{replay_hash}",
+        initials.as_deref().unwrap_or("")
+    );
+    let signature = hex(&sign(&subtle, &server.key, &message).await?);
+    let body = serde_json::to_string(&Submission {
+        score,
+        initials,
+        name: highscores::player_name(),
+        date,
+        mutators,
+        replay,
+        replay_hash,
+        signature,
+    })
+    .map_err(|e| e.to_string())?;
+    post("", &body).await
+}
+
+async fn sign(subtle: &SubtleCrypto, key: &str, message: &str) -> Result<Vec<u8>, JsValue> {
+    let algorithm = Object::new();
+    Reflect::set(&algorithm, &"name".into(), &"HMAC".into())?;
+    Reflect::set(&algorithm, &"hash".into(), &"SHA-256".into())?;
+    let raw = Uint8Array::from(key.as_bytes());
+    let key: CryptoKey = JsFuture::from(subtle.import_key_with_object(
+        "raw",
+        &raw,
+        &algorithm,
+        false,
+        &Array::of1(&"sign".into()),
+    )?)
+    .await?
+    .unchecked_into();
+    let signature =
+        JsFuture::from(subtle.sign_with_str_and_u8_array("HMAC", &key, message.as_bytes())?)
+            .await?;
+    Ok(Uint8Array::new(&signature).to_vec())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
@@ -1,44 +1,210 @@
 use std::cell::RefCell;
 use std::collections::VecDeque;
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use web_sys::{
-    CanvasRenderingContext2d, HtmlButtonElement, HtmlCanvasElement, HtmlElement, KeyboardEvent,
+    CanvasRenderingContext2d, Document, HtmlAnchorElement, HtmlButtonElement, HtmlCanvasElement,
+    HtmlImageElement, KeyboardEvent, MouseEvent,
 };
 
-const WIDTH: i32 = 20;
-const HEIGHT: i32 = 20;
-const CELL: f64 = 20.0;
+// browser-free game logic, also usable from native code; everything else
+// here is the web front end
+pub mod core;
+
+mod api;
+mod assets;
+mod audio;
+mod backdrop;
+mod boost;
+mod bus;
+mod callbacks;
+mod camera;
+mod correspondence;
+mod difficulty;
+mod editor;
+mod env;
+mod error;
+mod format;
+mod handicap;
+mod highscores;
+mod hud;
+mod idle;
+mod kiosk;
+mod layout;
+mod leaderboard;
+mod net;
+mod palette;
+mod particles;
+mod perf;
+mod privacy;
+mod quality;
+mod recorder;
+mod renderer;
+mod royale;
+mod runstats;
+mod save;
+mod schema;
+mod session;
+mod settings;
+mod sprites;
+mod storage;
+mod theme;
+mod tournament;
+mod transport;
+mod tween;
+#[cfg(feature = "dev")]
+mod visual;
+mod webgl;
+
+use crate::core::board::{self, Board, Event, Rules, Snake};
+use crate::core::bot::Personality;
+use crate::core::entity::{Entity, Kind};
+use crate::core::handicap::Handicap;
+use crate::core::headless::{self, MatchConfig};
+use crate::core::lockstep::Lockstep;
+use crate::core::perk::PERKS;
+use crate::core::plugin::{Plugin, Plugins, ScoreMultiplier, SpeedRamp};
+use crate::core::replay::{Ghost, Playback, Recording};
+use crate::core::rng::Rng;
+use crate::core::{bot, interp, names, path, HEIGHT, WIDTH};
+use backdrop::Backdrop;
+use boost::Boost;
+use bus::{GameEvent, Mode};
+use camera::Camera;
+use correspondence::Correspondence;
+use editor::ThemeEditor;
+use error::Error;
+use highscores::HighScores;
+use hud::{Content, Widget};
+use layout::Layout;
+use net::Msg;
+use particles::Particles;
+use renderer::{Backend, Quad, Renderer};
+use royale::{Royale, RoyaleConfig};
+use runstats::RunStats;
+use save::SavedRun;
+use theme::Theme;
+use tournament::Tournament;
+use tween::{Ease, Tween};
+
+// ticks skipped by one arrow press while watching a replay
+const SEEK_TICKS: u32 = 10;
+// turns the player can have waiting for the ticks to come
+const MAX_TURNS: usize = 3;
+// height of the score banner under a screenshot
+const SHOT_BANNER: f64 = 32.0;
+// how long the 3-2-1 before play lasts
+const COUNTDOWN_MS: f64 = 3000.0;
+// steps a crashed snake takes to flash and then dissolve, tail first,
+// before the game-over screen
+const DEATH_FRAMES: u32 = 12;
+const DISSOLVE_FRAMES: u32 = 8;
+// the snake's head swelling and easing back while it waits, and its tongue
+// flicking out now and then
+const BREATH: Tween = Tween::new(1.0, 1.12, 900.0, Ease::InOutSine).swinging(0.0);
+// one turn of a portal's swirl
+const PORTAL_SPIN_MS: f64 = 2000.0;
+const TONGUE: Tween = Tween::new(0.0, 1.0, 120.0, Ease::OutQuad).swinging(2600.0);
+// how hard the board shakes when a snake crashes and when one levels up,
+// in cells
+const CRASH_SHAKE: f64 = 0.6;
+const LEVEL_SHAKE: f64 = 0.25;
+// the grid and guide lines, faint on any background
+const GUIDE_COLOUR: &str = "rgba(128, 128, 128, 0.2)";
+const HINT_COLOUR: &str = "rgba(255, 215, 0, 0.6)";
+// longest name shown for the other player in versus play
+const MAX_NAME: usize = 20;
+// how early a frame may come and still be drawn, since the browser's frame
+// times jitter around the display's refresh
+const FRAME_SLACK_MS: f64 = 1.0;
+
+type FrameCallback = Closure<dyn FnMut(f64)>;
 
 thread_local! {
-    static GAME: RefCell<Option<Game>> = RefCell::new(None);
+    static GAME: RefCell<Option<Game>> = const { RefCell::new(None) };
+    static CLOCK: RefCell<Option<Clock>> = const { RefCell::new(None) };
+}
+
+// The game loop's timer, and the tick length it runs at.
+struct Clock {
+    closure: Closure<dyn FnMut()>,
+    timer: i32,
+    tick_ms: u32,
 }
 
 #[wasm_bindgen(start)]
 pub fn start() -> Result<(), JsValue> {
     console_error_panic_hook::set_once();
-    let window = web_sys::window().unwrap();
-    let document = window.document().unwrap();
-    let canvas: HtmlCanvasElement = document.get_element_by_id("game").unwrap().dyn_into()?;
-    canvas.set_width((WIDTH as f64 * CELL) as u32);
-    canvas.set_height((HEIGHT as f64 * CELL) as u32);
-    let ctx = canvas
+    // under Node there is no page to set up, only the headless exports
+    if web_sys::window().is_none() {
+        return Ok(());
+    }
+    setup().map_err(|e| {
+        let message = JsValue::from_str(&e.to_string());
+        fail(e);
+        message
+    })
+}
+
+// Stops the game for good after an error it can't carry on from, and says
+// what happened on the canvas.
+fn fail(e: Error) {
+    CLOCK.with(|clock| {
+        if let (Some(c), Some(window)) = (clock.borrow_mut().take(), web_sys::window()) {
+            window.clear_interval_with_handle(c.timer);
+        }
+    });
+    GAME.with(|g| g.borrow_mut().take());
+    error::show(&e);
+}
+
+fn setup() -> Result<(), Error> {
+    settings::load();
+    storage::purge();
+    theme::watch_scheme()?;
+    sprites::load();
+    assets::face(
+        theme::PIXEL_FONT,
+        theme::PIXEL_FONT_URL,
+        js_sys::Date::now(),
+    );
+    assets::font(&theme::current(), js_sys::Date::now());
+    let window = web_sys::window().ok_or(Error::Missing("window"))?;
+    let document = window.document().ok_or(Error::Missing("document"))?;
+    let body = document.body().ok_or(Error::Missing("body"))?;
+    let canvas: HtmlCanvasElement = document
+        .get_element_by_id("game")
+        .and_then(|e| e.dyn_into().ok())
+        .ok_or(Error::Missing("#game canvas"))?;
+    layout::install_safe_area(&body)?;
+    let initial = layout::measure(&window, &body)?;
+    layout::apply(&canvas, &initial)?;
+    let ctx: CanvasRenderingContext2d = canvas
         .get_context("2d")?
-        .unwrap()
-        .dyn_into::<CanvasRenderingContext2d>()?;
-    let score_el: HtmlElement = document.get_element_by_id("score").unwrap().dyn_into()?;
-    let restart_btn: HtmlButtonElement =
-        document.get_element_by_id("restart").unwrap().dyn_into()?;
-    let game = Game::new(ctx.clone(), score_el.clone(), restart_btn.clone());
+        .and_then(|c| c.dyn_into().ok())
+        .ok_or(Error::Missing("2D canvas context"))?;
+    let restart_btn: HtmlButtonElement = document
+        .get_element_by_id("restart")
+        .and_then(|e| e.dyn_into().ok())
+        .ok_or(Error::Missing("#restart button"))?;
+    let backdrop = Backdrop::new(&document)?;
+    let mut game = Game::new(ctx.clone(), backdrop, restart_btn.clone(), &initial);
+    game.renderer = renderer::start(&document, settings::with(|s| s.renderer));
+    game.relayout(&initial);
+    if let Some(run) = save::take() {
+        game.resume(run);
+    }
     GAME.with(|g| g.borrow_mut().replace(game));
 
     // restart button
     {
         let btn = restart_btn.clone();
         let closure = Closure::wrap(Box::new(move || {
+            audio::unlock();
             GAME.with(|game| {
                 if let Some(g) = game.borrow_mut().as_mut() {
-                    g.reset();
+                    g.restart();
                 }
             });
         }) as Box<dyn FnMut()>);
@@ -49,154 +215,2499 @@ pub fn start() -> Result<(), JsValue> {
     // keyboard events
     {
         let doc = document.clone();
+        let focus = document.clone();
         let closure = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+            audio::unlock();
+            if kiosk::is_unlock_combo(&event) {
+                kiosk::toggle_unlock();
+                return;
+            }
+            // a locked kiosk only plays; its overlays and settings wait for
+            // the unlock combo
+            if !kiosk::locked() && shortcut(&event, &focus) {
+                return;
+            }
             let key = event.key();
+            // Shift still counts as a key press for the menus
+            if key == "Shift" {
+                with_game(|g| g.boost.hold(true));
+            }
             GAME.with(|game| {
                 if let Some(g) = game.borrow_mut().as_mut() {
-                    if key == "Enter" {
-                        if g.over {
-                            g.reset();
-                        }
-                    } else {
-                        g.change_dir(&key);
-                    }
+                    g.key(&key);
                 }
             });
+            if with_game(|g| std::mem::take(&mut g.share_summary)) == Some(true) {
+                session::copy_session_summary();
+            }
         }) as Box<dyn FnMut(_)>);
         doc.add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())?;
         closure.forget();
     }
 
-    // game loop
+    // letting go of Shift, or of the window while holding it, ends a boost
+    {
+        let closure = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+            if event.key() == "Shift" {
+                with_game(|g| g.boost.hold(false));
+            }
+        }) as Box<dyn FnMut(_)>);
+        document.add_event_listener_with_callback("keyup", closure.as_ref().unchecked_ref())?;
+        closure.forget();
+        let closure = Closure::wrap(Box::new(move || {
+            with_game(|g| g.boost.hold(false));
+        }) as Box<dyn FnMut()>);
+        window.add_event_listener_with_callback("blur", closure.as_ref().unchecked_ref())?;
+        closure.forget();
+    }
+
+    // the clock keeps ticking in a background tab, so a game pauses while
+    // nobody can see or steer it and counts back in on return
+    {
+        let doc = document.clone();
+        let closure = Closure::wrap(Box::new(move || {
+            let hidden = doc.hidden();
+            with_game(|g| g.seen(!hidden));
+        }) as Box<dyn FnMut()>);
+        document.add_event_listener_with_callback(
+            "visibilitychange",
+            closure.as_ref().unchecked_ref(),
+        )?;
+        closure.forget();
+        for (kind, seen) in [("blur", false), ("focus", true)] {
+            let closure = Closure::wrap(Box::new(move || {
+                with_game(|g| g.seen(seen));
+            }) as Box<dyn FnMut()>);
+            window.add_event_listener_with_callback(kind, closure.as_ref().unchecked_ref())?;
+            closure.forget();
+        }
+    }
+
+    // dragging along the time-travel debugger's timeline or the theme
+    // editor's sliders
+    {
+        let closure = Closure::wrap(Box::new(move |event: MouseEvent| {
+            if event.buttons() & 1 == 0 {
+                return;
+            }
+            let (x, y) = (f64::from(event.offset_x()), f64::from(event.offset_y()));
+            with_game(|g| g.drag(x, y));
+        }) as Box<dyn FnMut(_)>);
+        for kind in ["mousedown", "mousemove"] {
+            canvas.add_event_listener_with_callback(kind, closure.as_ref().unchecked_ref())?;
+        }
+        closure.forget();
+    }
+
+    // keep an unfinished run when the page goes away
     {
         let closure = Closure::wrap(Box::new(move || {
+            GAME.with(|game| {
+                if let Some(run) = game.borrow().as_ref().and_then(Game::snapshot) {
+                    save::store(&run);
+                }
+            });
+            net::disconnect();
+        }) as Box<dyn FnMut()>);
+        window.add_event_listener_with_callback("pagehide", closure.as_ref().unchecked_ref())?;
+        closure.forget();
+    }
+
+    // window resizes, orientation changes and going in and out of
+    // fullscreen
+    {
+        let win = window.clone();
+        let closure = Closure::wrap(Box::new(move || {
+            let Ok(l) = layout::measure(&win, &body) else {
+                return;
+            };
+            if layout::apply(&canvas, &l).is_err() {
+                return;
+            }
             GAME.with(|game| {
                 if let Some(g) = game.borrow_mut().as_mut() {
-                    g.update();
-                    g.draw().unwrap();
+                    g.relayout(&l);
                 }
             });
         }) as Box<dyn FnMut()>);
-        window.set_interval_with_callback_and_timeout_and_arguments_0(
+        for kind in ["resize", "orientationchange"] {
+            window.add_event_listener_with_callback(kind, closure.as_ref().unchecked_ref())?;
+        }
+        document.add_event_listener_with_callback(
+            "fullscreenchange",
             closure.as_ref().unchecked_ref(),
-            100,
         )?;
         closure.forget();
     }
+
+    run_clock()?;
+
+    // render loop, throttled by the quality settings
+    {
+        quality::watch_battery(&window.navigator());
+        let frame: Rc<RefCell<Option<FrameCallback>>> = Rc::new(RefCell::new(None));
+        let next = frame.clone();
+        let win = window.clone();
+        let mut last = f64::NEG_INFINITY;
+        *frame.borrow_mut() = Some(Closure::wrap(Box::new(move |now: f64| {
+            let frame_ms = quality::frame_ms();
+            if now - last >= frame_ms - FRAME_SLACK_MS {
+                // kept on the frame interval's beat, unless the page fell
+                // behind by more than a frame
+                last = if now - last < 2.0 * frame_ms {
+                    last + frame_ms
+                } else {
+                    now
+                };
+                let drawn = GAME.with(|game| match game.borrow().as_ref() {
+                    Some(g) => {
+                        let span = perf::begin(perf::Part::Draw);
+                        let drawn = g.draw();
+                        span.end(frame_ms);
+                        drawn
+                    }
+                    None => Ok(()),
+                });
+                // a frame that can't be drawn won't draw next time either
+                if let Err(e) = drawn {
+                    fail(e.into());
+                    return;
+                }
+            }
+            if let Some(cb) = next.borrow().as_ref() {
+                let _ = win.request_animation_frame(cb.as_ref().unchecked_ref());
+            }
+        }) as Box<dyn FnMut(f64)>));
+        if let Some(cb) = frame.borrow().as_ref() {
+            window.request_animation_frame(cb.as_ref().unchecked_ref())?;
+        };
+    }
     Ok(())
 }
 
+// Handles a key that toggles an overlay, a setting or the autopilot rather
+// than playing, and says whether it was one.
+fn shortcut(event: &KeyboardEvent, document: &Document) -> bool {
+    let key = event.key();
+    // typing in one of the page's controls, where Tab moves between them
+    let typing = document
+        .active_element()
+        .is_some_and(|e| e.tag_name() != "BODY");
+    match key.as_str() {
+        "F3" => settings::update(|s| s.debug_hud = !s.debug_hud),
+        "Tab" if !typing => settings::update(|s| s.stats_panel = !s.stats_panel),
+        "F4" => {
+            with_game(Game::time_travel);
+        }
+        "F2" => {
+            with_game(Game::toggle_autopilot);
+        }
+        "F8" => {
+            with_game(Game::theme_editor);
+        }
+        _ => {
+            // G, F and M are free for the grid, fullscreen and the renderer
+            // unless they steer or are being typed
+            let free = !typing && settings::with(|s| s.bindings.dir(&key).is_none());
+            match key.to_ascii_lowercase().as_str() {
+                "g" if free => settings::update(|s| s.guides = !s.guides),
+                "f" if free => layout::toggle_fullscreen(),
+                "m" if free => {
+                    let next = match settings::with(|s| s.renderer) {
+                        Backend::Canvas => Backend::Webgl,
+                        Backend::Webgl => Backend::Canvas,
+                    };
+                    let _ = set_renderer(next.name());
+                }
+                _ => return false,
+            }
+            return true;
+        }
+    }
+    event.prevent_default();
+    true
+}
+
+// Starts the game loop, or restarts its timer if the tick length in the
+// settings has changed since. The loop checks after every tick, so a
+// change from anywhere takes hold by the next one.
+fn run_clock() -> Result<(), JsValue> {
+    let window = web_sys::window().ok_or("no window")?;
+    let tick_ms = settings::with(|s| s.tick_ms);
+    CLOCK.with(|clock| {
+        let mut clock = clock.borrow_mut();
+        if let Some(c) = clock.as_mut() {
+            if c.tick_ms != tick_ms {
+                window.clear_interval_with_handle(c.timer);
+                c.timer = window.set_interval_with_callback_and_timeout_and_arguments_0(
+                    c.closure.as_ref().unchecked_ref(),
+                    tick_ms as i32,
+                )?;
+                c.tick_ms = tick_ms;
+            }
+            return Ok(());
+        }
+        let closure = Closure::wrap(Box::new(move || {
+            let calls = GAME.with(|game| {
+                let mut game = game.borrow_mut();
+                let Some(g) = game.as_mut() else {
+                    return Vec::new();
+                };
+                let span = perf::begin(perf::Part::Update);
+                g.step();
+                let calls = g.deliver();
+                span.end(f64::from(settings::with(|s| s.tick_ms)));
+                audio::music(g.music_cue());
+                calls
+            });
+            callbacks::fire(calls);
+            let _ = run_clock();
+        }) as Box<dyn FnMut()>);
+        let timer = window.set_interval_with_callback_and_timeout_and_arguments_0(
+            closure.as_ref().unchecked_ref(),
+            tick_ms as i32,
+        )?;
+        *clock = Some(Clock {
+            closure,
+            timer,
+            tick_ms,
+        });
+        Ok(())
+    })
+}
+
+/// Milliseconds per game tick, from 40 to 400. It takes effect at once,
+/// mid-game too, and is kept for later visits.
+#[wasm_bindgen]
+pub fn set_tick_ms(tick_ms: u32) -> Result<(), JsValue> {
+    settings::update(|s| s.tick_ms = tick_ms.clamp(settings::MIN_TICK_MS, settings::MAX_TICK_MS));
+    run_clock()
+}
+
+/// What draws the board: `"canvas"` or `"webgl"`, which M also switches
+/// between. It takes effect at once, mid-game too, and is kept for later
+/// visits. Without WebGL the 2D canvas draws it anyway.
+#[wasm_bindgen]
+pub fn set_renderer(backend: &str) -> Result<(), JsValue> {
+    let backend = Backend::parse(backend).ok_or("unknown renderer")?;
+    settings::update(|s| s.renderer = backend);
+    with_game(|g| g.use_renderer(backend));
+    Ok(())
+}
+
+fn with_game<R>(f: impl FnOnce(&mut Game) -> R) -> Option<R> {
+    GAME.with(|game| game.borrow_mut().as_mut().map(f))
+}
+
+/// The last finished single-player run in the compact replay format.
+#[wasm_bindgen]
+pub fn export_replay() -> Option<String> {
+    with_game(|g| g.last.as_ref().map(Recording::encode)).flatten()
+}
+
+/// A PNG of the last single-player run's best moment, as a data URL: the
+/// board at its biggest combo or narrowest escape, with a caption.
+#[wasm_bindgen]
+pub fn best_moment() -> Option<String> {
+    with_game(|g| g.moment.clone()).flatten()
+}
+
+/// A PNG of the board as it is now, with the score and today's date in a
+/// banner underneath, as a data URL. With `download` it is also saved as
+/// `snake.png`.
+#[wasm_bindgen]
+pub fn screenshot(download: bool) -> Result<String, JsValue> {
+    let url = with_game(|g| g.screenshot()).ok_or("no game")??;
+    if download {
+        self::download(&url, "snake.png")?;
+    }
+    Ok(url)
+}
+
+/// Opens the theme editor over the board, from the start or game-over
+/// screen.
+#[wasm_bindgen]
+pub fn open_theme_editor() {
+    with_game(|g| {
+        if g.editor.is_none() {
+            g.theme_editor();
+        }
+    });
+}
+
+/// Switches the board to watching an exported replay. Any key other than
+/// the arrows (seek and speed) returns to play.
+#[wasm_bindgen]
+pub fn play_replay(data: &str) -> Result<(), JsValue> {
+    let rec = Recording::decode(data).ok_or_else(|| JsValue::from_str("invalid replay"))?;
+    with_game(|g| g.watch(rec)).unwrap_or(Ok(()))
+}
+
+#[wasm_bindgen]
+pub fn replay_seek(tick: u32) {
+    with_game(|g| {
+        if let Some(pb) = g.playback.as_mut() {
+            pb.seek(tick);
+        }
+    });
+}
+
+#[wasm_bindgen]
+pub fn replay_speed(speed: f64) {
+    with_game(|g| {
+        if let Some(pb) = g.playback.as_mut() {
+            pb.set_speed(speed);
+        }
+    });
+}
+
+/// The event log of the current run as JSON, for debugging.
+#[wasm_bindgen]
+pub fn export_log() -> String {
+    with_game(|g| serde_json::to_string(&g.log).unwrap_or_default()).unwrap_or_default()
+}
+
+/// Takes back the last `ticks` ticks of a single-player run and pauses.
+/// Rewound runs stay out of the high scores.
+#[wasm_bindgen]
+pub fn rewind(ticks: u32) {
+    with_game(|g| g.rewind(ticks));
+}
+
+/// The local top-ten table as a JSON array of
+/// `{score, mode, date, initials}` objects.
+#[wasm_bindgen]
+pub fn get_high_scores() -> String {
+    with_game(|g| g.high_scores.to_json()).unwrap_or_else(|| "[]".to_string())
+}
+
+/// Starts a pass-and-play tournament for 2 to 8 named players, each playing
+/// one run on the same seed, under the rules set when it starts.
+#[wasm_bindgen]
+pub fn start_tournament(names: Vec<String>) -> Result<(), JsValue> {
+    let tournament = Tournament::new(names, random_seed(), settings::rules()).ok_or_else(|| {
+        JsValue::from_str(&format!(
+            "a tournament needs {} to {} players",
+            tournament::MIN_PLAYERS,
+            tournament::MAX_PLAYERS
+        ))
+    })?;
+    with_game(|g| g.start_tournament(tournament));
+    Ok(())
+}
+
+/// Turns on a built-in plugin for single-player runs: `"speed_ramp"`,
+/// which speeds up with the score, or `"score_multiplier"`, which doubles
+/// the points for food. A plugin that is already on stays on once.
+#[wasm_bindgen]
+pub fn enable_plugin(name: &str) -> Result<(), JsValue> {
+    let plugin: Box<dyn Plugin> = match name {
+        "speed_ramp" => Box::new(SpeedRamp),
+        "score_multiplier" => Box::new(ScoreMultiplier { factor: 2 }),
+        _ => return Err(JsValue::from_str("unknown plugin")),
+    };
+    with_game(|g| {
+        if !g.plugins.has(plugin.name()) {
+            g.plugins.register(plugin);
+        }
+    });
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn clear_plugins() {
+    with_game(|g| g.plugins.clear());
+}
+
+/// Plays a versus round without drawing it, with the same rules as the
+/// game, for servers and bots running on wasm. `config` is a JSON
+/// `{seed, rules, handicaps, bots, max_ticks}` and the inputs are JSON arrays of
+/// `[dx, dy]` headings, one per tick, each a single step up, down, left or
+/// right. Returns `{scores, alive, ticks, winner}` as JSON.
+#[wasm_bindgen(js_name = run_match)]
+pub fn run_match_json(inputs_a: &str, inputs_b: &str, config: &str) -> Result<String, JsValue> {
+    let parse = |json: &str| serde_json::from_str::<Vec<(i32, i32)>>(json);
+    let inputs_a = parse(inputs_a).map_err(|e| e.to_string())?;
+    let inputs_b = parse(inputs_b).map_err(|e| e.to_string())?;
+    if !inputs_a
+        .iter()
+        .chain(&inputs_b)
+        .all(|&dir| board::is_heading(dir))
+    {
+        return Err(JsValue::from_str(
+            "inputs must be [0, -1], [0, 1], [-1, 0] or [1, 0]",
+        ));
+    }
+    let config: MatchConfig = serde_json::from_str(config).map_err(|e| e.to_string())?;
+    if !config.handicaps.iter().all(Handicap::valid) {
+        return Err(JsValue::from_str("invalid handicap"));
+    }
+    if !config.rules.valid() {
+        return Err(JsValue::from_str("invalid rules"));
+    }
+    let result = headless::run_match(&inputs_a, &inputs_b, &config);
+    Ok(serde_json::to_string(&result).unwrap_or_default())
+}
+
+/// Starts a roguelike run, where each new level pauses for a choice of
+/// perks that last the rest of the run. Passing the seed another run
+/// returned replays its food; leave it out for a fresh one. Returns the
+/// run's seed for sharing.
+#[wasm_bindgen]
+pub fn start_roguelike(seed: Option<String>) -> Result<String, JsValue> {
+    let seed = match seed {
+        Some(text) => {
+            u64::from_str_radix(text.trim(), 16).map_err(|_| JsValue::from_str("invalid seed"))?
+        }
+        None => random_seed(),
+    };
+    with_game(|g| g.start_roguelike(seed));
+    Ok(format!("{seed:x}"))
+}
+
+/// Starts the attract demo with the cycle bot, which plays the board out
+/// without a mistake, for watching or as a stress test of long snakes. Any
+/// key ends it, as with the usual demo.
+#[wasm_bindgen]
+pub fn start_autopilot() {
+    with_game(|g| g.start_attract(Some(Personality::Cycle)));
+}
+
+/// Starts a battle royale against bots in an arena whose outer ring closes
+/// every so often, until one snake is left. `config` is a JSON
+/// `{opponents, shrink_secs, min_side}`, any of which can be left out:
+/// three opponents, a ring every 10 seconds and a 6-cell arena at the end.
+#[wasm_bindgen]
+pub fn start_royale(config: Option<String>) -> Result<(), JsValue> {
+    let config: RoyaleConfig = match config {
+        Some(json) => serde_json::from_str(&json).map_err(|e| e.to_string())?,
+        None => RoyaleConfig::default(),
+    };
+    if !config.valid() {
+        return Err(JsValue::from_str(&format!(
+            "a battle royale needs 1 to {} opponents, a positive interval and a 2 to {} cell arena",
+            royale::MAX_OPPONENTS,
+            WIDTH.min(HEIGHT)
+        )));
+    }
+    with_game(|g| g.start_royale(config));
+    Ok(())
+}
+
+fn random_seed() -> u64 {
+    (js_sys::Math::random() * (1u64 << 53) as f64) as u64
+}
+
+fn solo_snake() -> Snake {
+    Snake::new((WIDTH / 2, HEIGHT / 2), (1, 0))
+}
+
+// Has the browser save `url` as a file called `name`.
+fn download(url: &str, name: &str) -> Result<(), JsValue> {
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or("no document")?;
+    let link: HtmlAnchorElement = document.create_element("a")?.dyn_into()?;
+    link.set_href(url);
+    link.set_download(name);
+    link.click();
+    Ok(())
+}
+
+// Which screen the game is on. Input, stepping and drawing each branch on
+// this rather than piecing it together from the board and the pause flags.
+#[derive(Clone, Copy, PartialEq)]
+enum AppState {
+    // nothing has been played since the page loaded
+    Menu,
+    // counting down to play, until the given time
+    Countdown(f64),
+    Playing,
+    Paused(Pause),
+    // the run has ended and the crashed snakes are going, for this many
+    // more steps
+    Dying { frames_left: u32 },
+    GameOver,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Pause {
+    // the viewport is too cramped to play in
+    Rotate,
+    Idle,
+    // waiting for the next tournament player to take the device
+    Handover,
+    // a roguelike run reached a new level and waits for a perk
+    Perk,
+    // the time-travel debugger is showing the run as it was at `scrub`
+    TimeTravel,
+    // the theme editor is open, over the start or game-over screen
+    Editor,
+    // the tab was hidden or the window lost focus mid-game
+    Hidden,
+}
+
 struct Game {
     ctx: CanvasRenderingContext2d,
-    snake: VecDeque<(i32, i32)>,
-    dir: (i32, i32),
-    food: (i32, i32),
-    score: i32,
-    over: bool,
-    score_el: HtmlElement,
+    backdrop: Backdrop,
+    board: Board,
+    // every event applied to `board` since the run began
+    log: Vec<Event>,
+    // the snakes as they were before the latest tick, and when it ran
+    prev_snakes: Vec<Snake>,
+    ticked_at: f64,
+    // the current run was rewound at least once
+    rewound: bool,
+    // index of the snake steered from this browser
+    local: usize,
+    // heading requested locally, sent to the peer in versus play
+    wish: (i32, i32),
+    // the player's next turns, one applied per tick, so two quick presses
+    // can't add up to a reversal and a fast zig-zag isn't lost
+    turns: VecDeque<(i32, i32)>,
+    lockstep: Option<Lockstep>,
+    tournament: Option<Tournament>,
+    // a slow match that only moves when the player does
+    correspondence: Option<Correspondence>,
+    // picks perks between levels and speeds up as it goes
+    roguelike: bool,
+    // bots to outlast in a shrinking arena
+    royale: Option<Royale>,
+    rng: Rng,
+    plugins: Plugins,
+    // ticks owed at the plugins' speed, carried between game clock ticks
+    pace: f64,
+    // best single-player run this session, raced as a ghost
+    best: Option<Recording>,
+    ghost: Option<Ghost>,
+    // the most recent finished single-player run, for export
+    last: Option<Recording>,
+    playback: Option<Playback>,
+    high_scores: HighScores,
+    // table position reached by the run that just ended
+    new_rank: Option<usize>,
+    over_at: f64,
+    state: AppState,
+    // tick shown by the time-travel debugger
+    scrub: u32,
+    editor: Option<ThemeEditor>,
+    // figures for the game-over screen
+    run: RunStats,
+    // the run's best moment, rendered as a data URL when it ends
+    moment: Option<String>,
+    // C was pressed for the session summary, which goes out once the game
+    // is let go, since the page's `on_summary` may call back into it
+    share_summary: bool,
+    // the board is running a bot demo until someone presses a key
+    attract: bool,
+    // a throwaway game, such as a visual report's, that leaves today's
+    // games, the best moment and the high scores alone
+    unrecorded: bool,
+    // sparks from food being eaten, drawn over the board
+    particles: Particles,
+    camera: Camera,
+    // Shift to run faster for as long as the stamina lasts
+    boost: Boost,
+    // draws the board's cells in place of the 2D canvas, if one was picked
+    renderer: Option<Box<dyn Renderer>>,
+    // what the demo's bot is called, and how it plays
+    bot: String,
+    personality: Personality,
+    // what the other player in versus play is called, once they say
+    rival: Option<String>,
+    // the mode last announced on the bus
+    mode: Mode,
+    last_input: f64,
+    // CSS pixels to a cell, and device pixels to a CSS pixel
+    cell: f64,
+    scale: f64,
+    // the score line drawn along the top of the board
+    hud: String,
     restart_btn: HtmlButtonElement,
 }
 
 impl Game {
     fn new(
         ctx: CanvasRenderingContext2d,
-        score_el: HtmlElement,
+        backdrop: Backdrop,
         restart_btn: HtmlButtonElement,
+        layout: &Layout,
     ) -> Self {
-        let mut snake = VecDeque::new();
-        snake.push_back((WIDTH / 2, HEIGHT / 2));
-        let food = (
-            (js_sys::Math::random() * WIDTH as f64) as i32,
-            (js_sys::Math::random() * HEIGHT as f64) as i32,
-        );
         let mut game = Self {
             ctx,
-            snake,
-            dir: (1, 0),
-            food,
-            score: 0,
-            over: false,
-            score_el,
+            backdrop,
+            board: Board::new(Vec::new(), Rules::default()),
+            log: Vec::new(),
+            prev_snakes: Vec::new(),
+            ticked_at: 0.0,
+            rewound: false,
+            local: 0,
+            wish: (1, 0),
+            turns: VecDeque::new(),
+            lockstep: None,
+            tournament: None,
+            correspondence: None,
+            roguelike: false,
+            royale: None,
+            rng: Rng::new(random_seed()),
+            plugins: Plugins::default(),
+            pace: 0.0,
+            best: None,
+            ghost: None,
+            last: None,
+            playback: None,
+            high_scores: HighScores::load(),
+            new_rank: None,
+            over_at: 0.0,
+            state: AppState::Menu,
+            scrub: 0,
+            editor: None,
+            run: RunStats::default(),
+            moment: None,
+            share_summary: false,
+            attract: false,
+            unrecorded: false,
+            particles: Particles::default(),
+            camera: Camera::default(),
+            boost: Boost::default(),
+            renderer: None,
+            bot: String::new(),
+            personality: Personality::Greedy,
+            rival: None,
+            mode: Mode::Solo,
+            last_input: 0.0,
+            cell: layout.cell,
+            scale: layout.scale,
+            hud: String::new(),
             restart_btn,
         };
-        game.update_score();
-        let _ = game.restart_btn.style().set_property("display", "none");
+        game.relayout(layout);
+        game.reset();
+        if matches!(game.state, AppState::Countdown(_)) {
+            game.state = AppState::Menu;
+        }
         game
     }
 
     fn reset(&mut self) {
-        self.snake.clear();
-        self.snake.push_back((WIDTH / 2, HEIGHT / 2));
-        self.dir = (1, 0);
-        self.food = (
-            (js_sys::Math::random() * WIDTH as f64) as i32,
-            (js_sys::Math::random() * HEIGHT as f64) as i32,
-        );
-        self.score = 0;
-        self.over = false;
+        self.lockstep = None;
+        self.correspondence = None;
+        self.roguelike = false;
+        self.royale = None;
+        self.attract = false;
+        if let Some(t) = &self.tournament {
+            self.rng = Rng::new(t.seed);
+            let snake = solo_snake().with_handicap(t.current_handicap(), &t.rules);
+            self.begin(vec![snake], 0, t.rules);
+            self.state = AppState::Paused(Pause::Handover);
+            return;
+        }
+        self.begin(vec![solo_snake()], 0, settings::rules());
+        self.ghost = self.best.clone().map(|rec| Ghost::new(rec, solo_snake()));
+    }
+
+    fn start_tournament(&mut self, tournament: Tournament) {
+        if self.lockstep.is_some() {
+            return;
+        }
+        self.tournament = Some(tournament);
+        self.reset();
+    }
+
+    fn start_versus(&mut self, seed: u64, local: usize, handicaps: [Handicap; 2], rules: Rules) {
+        self.tournament = None;
+        self.correspondence = None;
+        self.roguelike = false;
+        self.royale = None;
+        self.rng = Rng::new(seed);
+        self.lockstep = Some(Lockstep::new(local));
+        self.attract = false;
+        self.begin(headless::versus_snakes(handicaps, &rules), local, rules);
+    }
+
+    fn start_roguelike(&mut self, seed: u64) {
+        if self.lockstep.is_some() {
+            return;
+        }
+        self.tournament = None;
+        self.reset();
+        self.rng = Rng::new(seed);
+        self.begin(vec![solo_snake()], 0, settings::rules());
+        self.roguelike = true;
+        self.update_score();
+    }
+
+    fn start_royale(&mut self, config: RoyaleConfig) {
+        if self.lockstep.is_some() {
+            return;
+        }
+        self.tournament = None;
+        self.reset();
+        let rules = Rules {
+            shrink: config.shrink(settings::with(|s| s.tick_ms)),
+            ..settings::rules()
+        };
+        self.begin(config.snakes(), 0, rules);
+        self.royale = Some(Royale::new(config, &mut self.rng));
+        self.update_score();
+    }
+
+    // Rebuilds this side's run from its moves, then plays back what the
+    // opponent did since the last visit.
+    fn start_correspondence(&mut self, c: Correspondence) {
+        if self.lockstep.is_some() {
+            return;
+        }
+        self.tournament = None;
+        self.attract = false;
+        self.roguelike = false;
+        self.royale = None;
+        self.begin(vec![solo_snake()], 0, c.game.rules);
+        // each move is its own key press, so there's nothing to count in
+        if matches!(self.state, AppState::Countdown(_)) {
+            self.state = AppState::Playing;
+        }
+        let (board, rng, log) = c.game.play_out(c.slot);
+        let (rival, seen) = c.catch_up();
+        self.wish = board.snakes[0].dir;
+        self.board = board;
+        self.rng = rng;
+        self.log = log;
+        self.correspondence = Some(c);
+        self.update_score();
+        if self.board.over {
+            self.state = AppState::GameOver;
+            let _ = self.restart_btn.style().set_property("display", "block");
+        }
+        let mut pb = Playback::new(rival, solo_snake());
+        if pb.ticks() > seen {
+            pb.seek(seen);
+            self.playback = Some(pb);
+            self.hud = "Your rival's moves".to_string();
+        }
+    }
+
+    fn begin(&mut self, snakes: Vec<Snake>, local: usize, rules: Rules) {
+        self.wish = snakes[local].dir;
+        self.turns.clear();
+        self.boost.reset();
+        self.board = Board::new(snakes, rules);
+        self.run = RunStats::new(&self.board, local);
+        self.moment = None;
+        self.log.clear();
+        self.prev_snakes.clear();
+        self.rewound = false;
+        self.pace = 0.0;
+        self.local = local;
+        self.ghost = None;
+        if self.state != AppState::Paused(Pause::Rotate) {
+            self.count_in();
+        }
+        self.playback = None;
+        self.spawn_food();
+        self.last_input = js_sys::Date::now();
         self.update_score();
         let _ = self.restart_btn.style().set_property("display", "none");
     }
 
-    fn change_dir(&mut self, key: &str) {
-        if self.over {
+    // In versus play the host picks the seed for every round.
+    fn restart(&mut self) {
+        if let Some(config) = self.royale.as_ref().map(|r| r.config) {
+            self.start_royale(config);
+        } else if self.tournament.as_ref().is_some_and(Tournament::finished) {
+            self.tournament = None;
+            self.reset();
+        } else if self.lockstep.is_none() {
+            self.reset();
+        } else if net::is_host() {
+            self.rematch();
+        } else {
+            net::send(&Msg::Rematch);
+        }
+    }
+
+    fn rematch(&mut self) {
+        let seed = random_seed();
+        let handicaps = [handicap::lobby(0), handicap::lobby(1)];
+        let rules = settings::rules();
+        net::send(&Msg::Seed(seed, handicaps, rules));
+        self.start_versus(seed, 0, handicaps, rules);
+    }
+
+    // Starts the demo with `personality`, or one picked at random.
+    fn start_attract(&mut self, personality: Option<Personality>) {
+        self.lockstep = None;
+        self.correspondence = None;
+        self.roguelike = false;
+        self.royale = None;
+        self.tournament = None;
+        self.attract = true;
+        let mut rng = Rng::new(random_seed());
+        self.bot = names::generate(&mut rng);
+        self.personality = personality
+            .unwrap_or_else(|| Personality::ALL[rng.below(Personality::ALL.len() as i32) as usize]);
+        self.begin(vec![solo_snake()], 0, settings::rules());
+    }
+
+    // Drops the runs and scores kept in memory once the player has deleted
+    // their data, and goes back to the start screen.
+    fn forget(&mut self) {
+        self.high_scores = HighScores::load();
+        self.new_rank = None;
+        self.best = None;
+        self.last = None;
+        self.moment = None;
+        self.reset();
+        self.state = AppState::Menu;
+        self.update_score();
+    }
+
+    // A single-player run still in progress, which can be saved or rewound.
+    fn solo_run(&self) -> bool {
+        !self.board.over && self.solo()
+    }
+
+    // A plain single-player game, running or finished.
+    fn solo(&self) -> bool {
+        !(self.attract
+            || self.lockstep.is_some()
+            || self.tournament.is_some()
+            || self.correspondence.is_some()
+            || self.roguelike
+            || self.royale.is_some()
+            || self.playback.is_some())
+    }
+
+    // Whether the run is over for this browser's player: the board is, or
+    // their snake is out of a battle royale that would play on without it.
+    fn out(&self) -> bool {
+        self.board.over || self.board.snakes.get(self.local).is_some_and(|s| !s.alive)
+    }
+
+    fn snapshot(&self) -> Option<SavedRun> {
+        if !self.solo_run() {
+            return None;
+        }
+        Some(SavedRun {
+            board: self.board.clone(),
+            rng: self.rng.clone(),
+            log: self.log.clone(),
+            rewound: self.rewound,
+        })
+    }
+
+    // Resumed runs start paused so the player can get their bearings.
+    fn resume(&mut self, run: SavedRun) {
+        self.reset();
+        self.wish = run.board.snakes[0].dir;
+        self.turns.clear();
+        self.board = run.board;
+        self.rng = run.rng;
+        self.log = run.log;
+        self.rewound = run.rewound;
+        self.state = AppState::Paused(Pause::Idle);
+        self.update_score();
+    }
+
+    fn watch(&mut self, rec: Recording) -> Result<(), JsValue> {
+        if self.lockstep.is_some() {
+            return Err(JsValue::from_str("finish the match first"));
+        }
+        self.attract = false;
+        if self.state != AppState::Paused(Pause::Rotate) {
+            self.state = AppState::Playing;
+        }
+        self.playback = Some(Playback::new(rec, solo_snake()));
+        self.hud = "Replay".to_string();
+        let _ = self.restart_btn.style().set_property("display", "none");
+        Ok(())
+    }
+
+    // Rebuilds the board from the log up to an earlier tick. Food positions
+    // are part of the log, so everything before that tick plays out as before.
+    fn rewind(&mut self, ticks: u32) {
+        if !self.solo_run() {
             return;
         }
-        match key {
-            "ArrowUp" if self.dir.1 != 1 => self.dir = (0, -1),
-            "ArrowDown" if self.dir.1 != -1 => self.dir = (0, 1),
-            "ArrowLeft" if self.dir.0 != 1 => self.dir = (-1, 0),
-            "ArrowRight" if self.dir.0 != -1 => self.dir = (1, 0),
-            _ => {}
+        self.travel(self.board.tick.saturating_sub(ticks));
+        self.state = AppState::Paused(Pause::Idle);
+    }
+
+    // Cuts the run back to `target`, which counts as a rewind.
+    fn travel(&mut self, target: u32) {
+        self.log.truncate(self.cut(target));
+        self.board = Board::replay(vec![solo_snake()], self.board.rules, &self.log);
+        self.prev_snakes.clear();
+        self.wish = self.board.snakes[0].dir;
+        self.turns.clear();
+        self.run.best_moment = self.run.best_moment.filter(|m| m.tick <= target);
+        self.ghost = self
+            .best
+            .clone()
+            .map(|rec| Ghost::at(rec, solo_snake(), target));
+        self.rewound = true;
+        self.update_score();
+    }
+
+    // How much of the log it takes to reach `tick`.
+    fn cut(&self, tick: u32) -> usize {
+        self.log
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| **e == Event::TickAdvanced)
+            .nth(tick as usize)
+            .map_or(self.log.len(), |(i, _)| i)
+    }
+
+    // Opens or closes the time-travel debugger. Closing it without
+    // branching carries on from where the run was.
+    fn time_travel(&mut self) {
+        if self.state == AppState::Paused(Pause::TimeTravel) {
+            self.unpause();
+        } else if self.solo() && matches!(self.state, AppState::Playing | AppState::GameOver) {
+            self.scrub = self.board.tick;
+            self.state = AppState::Paused(Pause::TimeTravel);
+        }
+    }
+
+    // Hands steering to the bot, or takes it back. The switch goes in the
+    // run's log, so the replay shows which stretches the bot played.
+    fn toggle_autopilot(&mut self) {
+        if self.solo() && matches!(self.state, AppState::Countdown(_) | AppState::Playing) {
+            let on = !self.board.snakes[self.local].autopilot;
+            self.turns.clear();
+            self.dispatch(Event::AutopilotSet {
+                snake: self.local,
+                on,
+            });
+        }
+    }
+
+    // Whether the bot steered any part of this run.
+    fn assisted(&self) -> bool {
+        self.log
+            .iter()
+            .any(|e| matches!(e, Event::AutopilotSet { on: true, .. }))
+    }
+
+    // A press or drag with the mouse at `(x, y)` on the canvas. It moves
+    // the debugger along its timeline, or the editor's sliders.
+    fn drag(&mut self, x: f64, y: f64) {
+        let (w, h) = (WIDTH as f64 * self.cell, HEIGHT as f64 * self.cell);
+        if self.state == AppState::Paused(Pause::TimeTravel) {
+            let at = (x / w).clamp(0.0, 1.0);
+            self.scrub = (at * f64::from(self.board.tick)).round() as u32;
+        } else if let Some(editor) = self.editor.as_mut() {
+            editor.drag(x, y, w, h);
+        }
+    }
+
+    // Opens or closes the theme editor. It only opens between games, as
+    // the board is needed for the preview.
+    fn theme_editor(&mut self) {
+        if self.editor.is_some() {
+            self.close_editor();
+        } else if matches!(self.state, AppState::Menu | AppState::GameOver)
+            && !self.attract
+            && !kiosk::enabled()
+            && self.playback.is_none()
+        {
+            self.editor = Some(ThemeEditor::new(theme::current(), &self.ctx));
+            self.state = AppState::Paused(Pause::Editor);
+        }
+    }
+
+    fn close_editor(&mut self) {
+        self.editor = None;
+        self.state = if self.board.over {
+            AppState::GameOver
+        } else {
+            AppState::Menu
+        };
+    }
+
+    // Continues live from the tick the debugger shows, dropping everything
+    // after it. This also brings a finished run back to life.
+    fn branch(&mut self) {
+        self.travel(self.scrub);
+        self.unpause();
+        if !self.board.over {
+            let _ = self.restart_btn.style().set_property("display", "none");
         }
     }
 
-    fn update(&mut self) {
-        if self.over {
+    // Swaps what draws the board, keeping the run as it is.
+    fn use_renderer(&mut self, backend: Backend) {
+        let Some(document) = web_sys::window().and_then(|w| w.document()) else {
             return;
+        };
+        self.renderer = renderer::start(&document, backend);
+        if let Some(renderer) = &self.renderer {
+            let size = (WIDTH as f64 * self.cell, HEIGHT as f64 * self.cell);
+            renderer.resize(size, self.scale);
         }
-        let mut new_head = *self.snake.front().unwrap();
-        new_head.0 = (new_head.0 + self.dir.0 + WIDTH) % WIDTH;
-        new_head.1 = (new_head.1 + self.dir.1 + HEIGHT) % HEIGHT;
-        if self.snake.contains(&new_head) {
-            self.over = true;
-            let _ = self.restart_btn.style().set_property("display", "block");
+    }
+
+    fn relayout(&mut self, layout: &Layout) {
+        self.cell = layout.cell;
+        self.scale = layout.scale;
+        let size = (WIDTH as f64 * layout.cell, HEIGHT as f64 * layout.cell);
+        self.backdrop.resize(size, layout.scale);
+        if let Some(renderer) = &self.renderer {
+            renderer.resize(size, layout.scale);
+        }
+        if !layout.usable {
+            self.state = AppState::Paused(Pause::Rotate);
+        } else if self.state == AppState::Paused(Pause::Rotate) {
+            self.unpause();
+        }
+    }
+
+    // Pauses a game in play when the page goes out of sight or focus, and
+    // counts it back in when the page returns. Games over the network
+    // carry on, since the other side can't be paused.
+    fn seen(&mut self, seen: bool) {
+        let pausable = !(self.attract
+            || self.lockstep.is_some()
+            || self.correspondence.is_some()
+            || self.playback.is_some());
+        if !seen && pausable && matches!(self.state, AppState::Countdown(_) | AppState::Playing) {
+            self.turns.clear();
+            self.state = AppState::Paused(Pause::Hidden);
+        } else if seen && self.state == AppState::Paused(Pause::Hidden) {
+            self.unpause();
+        }
+    }
+
+    // Leaves a pause for whichever screen the board calls for.
+    fn unpause(&mut self) {
+        if self.out() {
+            self.game_over();
+        } else {
+            self.count_in();
+        }
+    }
+
+    fn game_over(&mut self) {
+        self.state = AppState::GameOver;
+        let _ = self.restart_btn.style().set_property("display", "block");
+    }
+
+    // Counts down before play starts or resumes, so the player has time to
+    // see where the snake is heading. The demo starts straight away.
+    fn count_in(&mut self) {
+        self.state = if self.attract {
+            AppState::Playing
+        } else {
+            AppState::Countdown(js_sys::Date::now() + COUNTDOWN_MS)
+        };
+    }
+
+    fn key(&mut self, key: &str) {
+        self.last_input = js_sys::Date::now();
+        if let Some(pb) = self.playback.as_mut() {
+            let tick = pb.ghost.tick();
+            match key {
+                "ArrowLeft" => pb.seek(tick.saturating_sub(SEEK_TICKS)),
+                "ArrowRight" => pb.seek(tick + SEEK_TICKS),
+                "ArrowUp" => pb.set_speed(pb.speed() * 2.0),
+                "ArrowDown" => pb.set_speed(pb.speed() / 2.0),
+                _ if self.correspondence.is_some() => {
+                    self.playback = None;
+                    self.update_score();
+                }
+                _ => self.reset(),
+            }
+        } else if self.attract {
+            self.reset();
+        } else if self.loading() {
+            // play waits for the assets
+        } else {
+            match self.state {
+                AppState::Menu
+                | AppState::Paused(Pause::Idle | Pause::Handover | Pause::Hidden) => {
+                    self.count_in();
+                }
+                AppState::Paused(Pause::Perk) => self.pick_perk(key),
+                AppState::Paused(Pause::TimeTravel) => match key {
+                    "ArrowLeft" => self.scrub = self.scrub.saturating_sub(1),
+                    "ArrowRight" => self.scrub = (self.scrub + 1).min(self.board.tick),
+                    "Enter" => self.branch(),
+                    "Escape" => self.time_travel(),
+                    _ => {}
+                },
+                AppState::Paused(Pause::Editor) => match key {
+                    "Enter" => {
+                        if let Some(editor) = self.editor.take() {
+                            theme::save(editor.theme);
+                        }
+                        self.close_editor();
+                    }
+                    "Escape" => self.close_editor(),
+                    _ => {
+                        if let Some(editor) = self.editor.as_mut() {
+                            editor.key(key);
+                        }
+                    }
+                },
+                AppState::Paused(Pause::Rotate) => {}
+                AppState::GameOver if key == "Enter" || key.eq_ignore_ascii_case("r") => {
+                    self.restart();
+                }
+                AppState::GameOver if key.eq_ignore_ascii_case("c") => {
+                    self.share_summary = true;
+                }
+                AppState::GameOver if key.eq_ignore_ascii_case("s") => {
+                    let _ = self.save_moment();
+                }
+                AppState::GameOver
+                    if key == "Escape" && self.lockstep.is_none() && self.tournament.is_none() =>
+                {
+                    self.reset();
+                    self.state = AppState::Menu;
+                }
+                AppState::Dying { .. } | AppState::GameOver => {}
+                AppState::Countdown(_) | AppState::Playing => self.change_dir(key),
+            }
+        }
+    }
+
+    // Perks are picked with the number keys, in the order they're listed.
+    fn pick_perk(&mut self, key: &str) {
+        let Some(&perk) = key
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| PERKS.get(n.wrapping_sub(1)))
+        else {
+            return;
+        };
+        let snake = self.local;
+        self.dispatch(Event::PerkGained { snake, perk });
+        self.count_in();
+    }
+
+    fn change_dir(&mut self, key: &str) {
+        if !matches!(self.state, AppState::Countdown(_) | AppState::Playing)
+            || self.board.snakes[self.local].autopilot
+        {
             return;
         }
-        if new_head == self.food {
-            self.score += 1;
+        let Some(dir) = settings::with(|s| s.bindings.dir(key)) else {
+            return;
+        };
+        let turns = self.board.rules.quarter_turns(self.board.tick);
+        let dir = Rules::unrotate(dir, turns);
+        if let Some(c) = self.correspondence.as_mut() {
+            if c.moves_left() == 0 {
+                return;
+            }
+            c.play(dir);
+            self.dispatch(Event::TurnRequested { snake: 0, dir });
+            self.tick();
             self.update_score();
-            self.food = (
-                (js_sys::Math::random() * WIDTH as f64) as i32,
-                (js_sys::Math::random() * HEIGHT as f64) as i32,
-            );
+        } else if self.lockstep.is_some() {
+            self.wish = dir;
         } else {
-            self.snake.pop_back();
+            // checked against the turn before it, or the way the snake last
+            // moved, which is all the board has seen until the turns apply
+            let moved = self.board.snakes[self.local].dir;
+            let last = self.turns.back().copied().unwrap_or(moved);
+            if dir != last && dir != (-last.0, -last.1) && self.turns.len() < MAX_TURNS {
+                self.turns.push_back(dir);
+            }
+        }
+    }
+
+    // Ticks per step of the game clock, from the rules, plugins and perks.
+    fn speed(&self) -> f64 {
+        let mut speed = self.board.rules.speed();
+        if self.plugged() {
+            speed *= self.plugins.speed(&self.board);
+            // the setting and the plugin are the same ramp, applied once
+            if settings::with(|s| s.speed_ramp) && !self.plugins.has(SpeedRamp.name()) {
+                speed *= SpeedRamp.speed(&self.board);
+            }
         }
-        self.snake.push_front(new_head);
+        if self.roguelike {
+            let snake = &self.board.snakes[self.local];
+            speed *= snake.perks.speed(snake.level());
+        }
+        if self.boost.active() {
+            speed *= boost::FACTOR;
+        }
+        speed
     }
 
-    fn update_score(&self) {
-        self.score_el
-            .set_text_content(Some(&format!("Score: {}", self.score)));
+    // How intense the music should be, or `None` for silence: it only
+    // plays while someone is playing, and speeds up with the game.
+    fn music_cue(&self) -> Option<f64> {
+        let playing = self.state == AppState::Playing && !self.attract && self.playback.is_none();
+        playing.then(|| self.speed().clamp(1.0, 3.0))
+    }
+
+    fn step(&mut self) {
+        let now = js_sys::Date::now();
+        if let Some(ready) = assets::poll(now) {
+            bus::emit(GameEvent::AssetsReady(ready));
+        }
+        self.particles.prune(now);
+        for msg in net::drain() {
+            self.handle(msg);
+        }
+        if let Some(pb) = self.playback.as_mut() {
+            if self.state == AppState::Playing {
+                pb.advance();
+            }
+            return;
+        }
+        let expired =
+            kiosk::enabled() && js_sys::Date::now() - self.over_at >= kiosk::AUTO_RESET_MS;
+        if self.state == AppState::GameOver && (self.attract || expired) {
+            self.start_attract(None);
+        }
+        if self.state == AppState::Menu && kiosk::enabled() {
+            self.check_idle();
+        }
+        if let AppState::Countdown(until) = self.state {
+            if js_sys::Date::now() >= until {
+                self.state = AppState::Playing;
+            }
+        }
+        if let AppState::Dying { frames_left } = self.state {
+            if frames_left == 0 {
+                self.over_at = js_sys::Date::now();
+                self.game_over();
+            } else {
+                self.state = AppState::Dying {
+                    frames_left: frames_left - 1,
+                };
+            }
+        }
+        if self.state != AppState::Playing || self.correspondence.is_some() {
+            return;
+        }
+        self.check_idle();
+        let bot = if self.attract {
+            Some(self.personality)
+        } else if self.board.snakes[self.local].autopilot {
+            Some(settings::with(|s| s.autopilot))
+        } else {
+            None
+        };
+        if let Some((personality, food)) = bot.zip(self.board.nearest_food(self.local)) {
+            self.turns.clear();
+            let dir = bot::steer(&self.board, self.local, food, personality);
+            self.dispatch(Event::TurnRequested {
+                snake: self.local,
+                dir,
+            });
+        }
+        if self.lockstep.is_some() {
+            net::sample();
+        }
+        let tick_ms = settings::with(|s| s.tick_ms) as f64;
+        // boosting stacks with perks and mutators, but the bot never boosts
+        let boostable =
+            self.plugged() && self.playback.is_none() && !self.board.snakes[self.local].autopilot;
+        self.boost.step(tick_ms, boostable);
+        let speed = self.speed();
+        self.run.played(tick_ms, speed);
+        self.pace += speed;
+        while self.pace >= 1.0 && !self.out() {
+            self.pace -= 1.0;
+            if !self.step_once() {
+                self.pace = 0.0;
+                break;
+            }
+        }
+    }
+
+    // Runs one tick, trading inputs with the peer first in versus play.
+    // Returns false when the peer's inputs haven't arrived yet.
+    fn step_once(&mut self) -> bool {
+        let Some(lockstep) = self.lockstep.as_mut() else {
+            let bots = self.royale.as_ref().map(|r| r.turns(&self.board));
+            for event in bots.into_iter().flatten() {
+                self.dispatch(event);
+            }
+            if let Some(dir) = self.turns.pop_front() {
+                let snake = self.local;
+                self.dispatch(Event::TurnRequested { snake, dir });
+            }
+            self.tick();
+            return true;
+        };
+        while let Some((tick, dir)) = lockstep.schedule(self.wish) {
+            net::send(&Msg::Input(tick, dir));
+        }
+        let Some(dirs) = lockstep.advance() else {
+            net::note_stall();
+            return false;
+        };
+        for (snake, dir) in dirs.into_iter().enumerate() {
+            self.dispatch(Event::TurnRequested { snake, dir });
+        }
+        self.tick();
+        true
+    }
+
+    fn check_idle(&mut self) {
+        let Some(limit) = idle::timeout_ms() else {
+            return;
+        };
+        if self.attract || self.lockstep.is_some() || self.board.snakes[self.local].autopilot {
+            return;
+        }
+        if js_sys::Date::now() - self.last_input < limit {
+            return;
+        }
+        if kiosk::enabled() {
+            self.start_attract(None);
+        } else {
+            self.state = AppState::Paused(Pause::Idle);
+        }
+    }
+
+    fn handle(&mut self, msg: Msg) {
+        match msg {
+            Msg::Open => {
+                net::send(&Msg::Name(highscores::player_name()));
+                if net::is_host() {
+                    self.rematch();
+                }
+            }
+            Msg::Name(name) => {
+                self.rival = Some(name.chars().take(MAX_NAME).collect());
+                self.update_score();
+            }
+            Msg::Seed(seed, handicaps, rules) => self.start_versus(seed, 1, handicaps, rules),
+            Msg::Input(tick, dir) => {
+                if let Some(lockstep) = self.lockstep.as_mut() {
+                    lockstep.receive(tick, dir);
+                }
+            }
+            Msg::Rematch if net::is_host() => self.rematch(),
+            Msg::Closed => {
+                self.rival = None;
+                self.reset();
+            }
+            _ => {}
+        }
+    }
+
+    // Plugins only change local runs, since a peer or a correspondence
+    // opponent wouldn't run the same ones.
+    fn plugged(&self) -> bool {
+        !(self.attract || self.lockstep.is_some() || self.correspondence.is_some())
+    }
+
+    fn mode(&self) -> Mode {
+        if self.playback.is_some() {
+            Mode::Replay
+        } else if self.attract {
+            Mode::Demo
+        } else if self.lockstep.is_some() {
+            Mode::Versus
+        } else if self.tournament.is_some() {
+            Mode::Tournament
+        } else if self.correspondence.is_some() {
+            Mode::Correspondence
+        } else if self.roguelike {
+            Mode::Roguelike
+        } else if self.royale.is_some() {
+            Mode::Royale
+        } else {
+            Mode::Solo
+        }
+    }
+
+    fn dispatch(&mut self, event: Event) {
+        let turning = match event {
+            Event::TurnRequested { snake, .. } => {
+                self.board.snakes.get(snake).map(|s| (snake, s.dir))
+            }
+            _ => None,
+        };
+        self.board.apply(&event);
+        self.log.push(event);
+        if let Some((snake, was)) = turning {
+            let dir = self.board.snakes[snake].dir;
+            if dir != was {
+                bus::emit(GameEvent::DirectionChanged { snake, dir });
+            }
+        }
+    }
+
+    // Acts on the bus's events and returns the page's callbacks for them,
+    // to be made once the game is no longer borrowed.
+    fn deliver(&mut self) -> Vec<callbacks::Call> {
+        let mut calls = Vec::new();
+        let mode = self.mode();
+        if mode != self.mode {
+            self.mode = mode;
+            bus::emit(GameEvent::ModeToggled(mode));
+        }
+        for event in bus::drain() {
+            match event {
+                GameEvent::FoodEaten { snake, score } => {
+                    if snake == self.local && !self.attract {
+                        audio::play(audio::Sound::Eat);
+                    }
+                    if let Some(&head) = self.board.snakes.get(snake).and_then(|s| s.body.front()) {
+                        let colour = palette::current().food;
+                        self.particles.burst(head, colour, js_sys::Date::now());
+                    }
+                    self.update_score();
+                    calls.push(callbacks::Call::Score(callbacks::Score {
+                        snake,
+                        local: snake == self.local,
+                        score,
+                    }));
+                }
+                GameEvent::LevelUp { snake, .. } => {
+                    if self.roguelike && snake == self.local && self.state == AppState::Playing {
+                        self.state = AppState::Paused(Pause::Perk);
+                    }
+                    self.camera.shake(LEVEL_SHAKE, js_sys::Date::now());
+                    self.update_score();
+                }
+                GameEvent::Died { snake } => {
+                    if snake == self.local && !self.attract {
+                        audio::play(audio::Sound::Crash);
+                    }
+                    self.camera.shake(CRASH_SHAKE, js_sys::Date::now());
+                }
+                GameEvent::Ticked { tick } => calls.push(callbacks::Call::Tick(callbacks::Tick {
+                    mode: self.mode,
+                    tick,
+                    scores: self.scores(),
+                })),
+                GameEvent::AssetsReady(ready) => calls.push(callbacks::Call::Ready(ready)),
+                GameEvent::GameOver => {
+                    calls.push(callbacks::Call::GameOver(callbacks::GameOver {
+                        mode: self.mode,
+                        score: self.board.snakes[self.local].score,
+                        scores: self.scores(),
+                        ticks: self.board.tick,
+                    }));
+                }
+                _ => {}
+            }
+        }
+        calls
+    }
+
+    fn stats(&self) -> api::GameStats {
+        let rival = self
+            .board
+            .snakes
+            .get(1 - self.local)
+            .filter(|_| self.lockstep.is_some());
+        api::GameStats {
+            mode: self.mode(),
+            score: self.board.snakes.get(self.local).map_or(0, |s| s.score),
+            rival_score: rival.map(|s| s.score),
+            tick: self.board.tick,
+            over: self.board.over,
+            paused: matches!(self.state, AppState::Paused(_)),
+            best: self.high_scores.entries().first().map(|e| e.score),
+        }
+    }
+
+    fn scores(&self) -> Vec<i32> {
+        self.board.snakes.iter().map(|s| s.score).collect()
+    }
+
+    // Announces what the tick that led here from `before` did.
+    fn announce(&self, before: &Board) {
+        bus::emit(GameEvent::Ticked {
+            tick: self.board.tick,
+        });
+        for (snake, now) in self.board.snakes.iter().enumerate() {
+            if self.board.ate(before, snake) {
+                bus::emit(GameEvent::FoodEaten {
+                    snake,
+                    score: now.score,
+                });
+            }
+            let level = now.level();
+            if level > before.snakes[snake].level() {
+                bus::emit(GameEvent::LevelUp { snake, level });
+            }
+            if self.board.died(before, snake) {
+                bus::emit(GameEvent::Died { snake });
+            }
+        }
+        let out = |board: &Board| board.over || !board.snakes[self.local].alive;
+        if out(&self.board) && !out(before) {
+            bus::emit(GameEvent::GameOver);
+        }
+    }
+
+    fn tick(&mut self) {
+        self.prev_snakes = self.board.snakes.clone();
+        self.ticked_at = js_sys::Date::now();
+        let before = self.board.clone();
+        self.dispatch(Event::TickAdvanced);
+        if self.plugged() {
+            for event in self.plugins.ticked(&before, &self.board) {
+                self.dispatch(event);
+            }
+        }
+        self.announce(&before);
+        self.run.ticked(&before, &self.board, self.local);
+        if self.out() {
+            self.state = AppState::Dying {
+                frames_left: DEATH_FRAMES,
+            };
+            if !self.attract && !self.unrecorded {
+                session::record(session::Run {
+                    score: self.board.snakes[self.local].score,
+                    length: self.run.max_length,
+                    mode: self.mode().name().to_string(),
+                    secs: (self.run.played_ms / 1000.0) as u32,
+                });
+            }
+            if self.solo() && !self.unrecorded {
+                self.moment = self.render_moment().ok().flatten();
+            }
+            self.finish_run();
+            return;
+        }
+        if let Some(ghost) = self.ghost.as_mut() {
+            if !ghost.step() {
+                self.ghost = None;
+            }
+        }
+        self.spawn_food();
+    }
+
+    // Tops the food up to what the rules call for.
+    fn spawn_food(&mut self) {
+        while let Some(mut event) = self.board.spawn_food(&mut self.rng) {
+            if let Event::FoodSpawned { pos } = &mut event {
+                if self.plugged() {
+                    *pos = self.plugins.spawn(&self.board, *pos);
+                }
+            }
+            self.dispatch(event);
+        }
+    }
+
+    fn finish_run(&mut self) {
+        if self.lockstep.is_some()
+            || self.attract
+            || self.unrecorded
+            || self.royale.is_some()
+            || self.rewound
+            || self.correspondence.is_some()
+            || self.assisted()
+        {
+            return;
+        }
+        let score = self.board.snakes[self.local].score;
+        if let Some(t) = self.tournament.as_mut() {
+            // tournament runs may be handicapped, so they stay out of the
+            // personal tables
+            t.record(score);
+            return;
+        }
+        let mutators = self.board.rules.mutators;
+        let mode = if self.roguelike { "roguelike" } else { "solo" };
+        self.new_rank = self.high_scores.submit(score, mode, &mutators);
+        let rec = Recording::new(score, self.board.rules, self.log.clone());
+        if score > 0 {
+            leaderboard::submit(score, &mutators, rec.encode());
+        }
+        if self.best.as_ref().is_none_or(|best| score > best.score) {
+            self.best = Some(rec.clone());
+        }
+        self.last = Some(rec);
+    }
+
+    fn update_score(&mut self) {
+        if let Some(c) = &self.correspondence {
+            let (rival, _, _) = c.game.play_out(c.rival());
+            let text = format!(
+                "You: {} \u{2014} Rival: {} \u{2014} {} moves left today",
+                format::score(self.board.snakes[0].score),
+                format::score(rival.snakes[0].score),
+                c.moves_left()
+            );
+            self.hud = text;
+            return;
+        }
+        let text = match self.board.snakes.get(1 - self.local) {
+            Some(rival) if self.lockstep.is_some() => {
+                let me = &self.board.snakes[self.local];
+                format!(
+                    "You: {}{} \u{2014} {}: {}{}",
+                    format::score(me.score),
+                    me.handicap.label(),
+                    self.rival.as_deref().unwrap_or("Rival"),
+                    format::score(rival.score),
+                    rival.handicap.label()
+                )
+            }
+            _ if self.attract => {
+                format!("Demo \u{2014} {} ({})", self.bot, self.personality.name())
+            }
+            _ if self.royale.is_some() => {
+                let (alive, total) = Royale::standing(&self.board);
+                format!(
+                    "Score: {} \u{2014} {alive} of {total} left",
+                    format::score(self.board.snakes[self.local].score)
+                )
+            }
+            _ if self.roguelike => {
+                let me = &self.board.snakes[self.local];
+                format!(
+                    "Level {} \u{2014} Score: {}",
+                    me.level() + 1,
+                    format::score(me.score)
+                )
+            }
+            _ => match self.tournament.as_ref().and_then(Tournament::current) {
+                Some(name) if !self.board.over => {
+                    format!(
+                        "{name} \u{2014} Score: {}",
+                        format::score(self.board.snakes[self.local].score)
+                    )
+                }
+                _ => format!(
+                    "Score: {}",
+                    format::score(self.board.snakes[self.local].score)
+                ),
+            },
+        };
+        self.hud = text;
     }
 
     fn draw(&self) -> Result<(), JsValue> {
-        self.ctx.set_fill_style(&JsValue::from_str("black"));
+        self.draw_at(js_sys::Date::now())
+    }
+
+    // Draws the frame for time `now`, which only moves the animations.
+    fn draw_at(&self, now: f64) -> Result<(), JsValue> {
+        let cell = self.cell;
+        let theme = theme::current();
+        // everything below is in CSS pixels; this is the one place they
+        // become device pixels
+        self.ctx
+            .set_transform(self.scale, 0.0, 0.0, self.scale, 0.0, 0.0)?;
+        if !self.backdrop.draw(&self.ctx, &theme, now)? {
+            self.ctx.set_fill_style_str(&theme.background);
+            self.ctx
+                .fill_rect(0.0, 0.0, WIDTH as f64 * cell, HEIGHT as f64 * cell);
+        }
+        if let Some(progress) = assets::progress().filter(|_| self.loading()) {
+            let size = (WIDTH as f64 * cell, HEIGHT as f64 * cell);
+            return assets::draw(&self.ctx, size, progress, &theme);
+        }
+        if let Some(editor) = &self.editor {
+            return self.draw_editor(editor);
+        }
+        if let Some(pb) = &self.playback {
+            return self.draw_playback(pb);
+        }
+        if self.state == AppState::Paused(Pause::TimeTravel) {
+            return self.draw_time_travel();
+        }
+        // a rotating board turns about its centre, and the board shakes
+        // on crashes and level-ups
+        self.ctx.save();
+        let turns = self.board.rules.quarter_turns(self.board.tick);
+        let size = (WIDTH as f64 * cell, HEIGHT as f64 * cell);
+        self.camera.apply(&self.ctx, size, cell, turns, now)?;
+        // Snakes glide from where they were at the last tick to where they
+        // are now, so they move smoothly rather than a cell at a time. The
+        // rival in versus play only moves when its inputs arrive, so it is
+        // carried on past the tick to hide the network's jitter.
+        let tick_ms = settings::with(|s| s.tick_ms) as f64;
+        let since = interp::progress(now - self.ticked_at, tick_ms);
+        let smooth = settings::with(|s| s.smooth);
+        // on still screens the snakes breathe, which only the clock drives
+        let idle = self.idle().then_some(now - self.ticked_at);
+        let glides: Vec<_> = self
+            .board
+            .snakes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, snake)| {
+                let prev = self.prev_snakes.get(i).unwrap_or(snake);
+                let remote = self.lockstep.is_some() && i != self.local;
+                let t = if remote { since } else { since.min(1.0) };
+                let crashed = self.dissolving(i).is_some();
+                (remote || smooth || crashed || idle.is_some())
+                    .then(|| (i, interp::segments(prev, snake, t)))
+            })
+            .collect();
+        if let Some(renderer) = &self.renderer {
+            renderer.draw(&self.ctx, &self.quads(&glides, &theme))?;
+            self.draw_hint()?;
+            self.particles.draw(&self.ctx, now, cell);
+            self.ctx.restore();
+            return self.draw_overlays(now, &theme);
+        }
+        if let Some(ghost) = &self.ghost {
+            self.ctx.set_global_alpha(0.3);
+            self.ctx.set_fill_style_str(&theme.ink);
+            for (x, y) in ghost.snake().body.iter() {
+                self.ctx
+                    .fill_rect(*x as f64 * cell, *y as f64 * cell, cell, cell);
+            }
+            self.ctx.set_global_alpha(1.0);
+        }
+        let skip: Vec<_> = glides.iter().map(|(i, _)| *i).collect();
+        self.draw_entities(&self.ctx, &self.board, &skip, &theme, now);
+        let sheet = sprites::sheet();
+        for (i, segments) in glides {
+            let shown = self.dissolving(i).unwrap_or(usize::MAX);
+            for (along, (x, y)) in segments.into_iter().enumerate().take(shown) {
+                let (x, y) = (x * cell, y * cell);
+                let size = match idle.filter(|_| along == 0) {
+                    Some(elapsed) => cell * BREATH.at(elapsed),
+                    None => cell,
+                };
+                let at = (x - (size - cell) / 2.0, y - (size - cell) / 2.0, size);
+                self.draw_segment(
+                    &self.ctx,
+                    &self.board,
+                    (i, along),
+                    at,
+                    &theme,
+                    sheet.as_ref(),
+                );
+                if let Some(elapsed) = idle.filter(|_| along == 0) {
+                    let snake = &self.board.snakes[i];
+                    let (_, turns) = sprites::segment(&snake.body, 0, snake.dir);
+                    let _ = sprites::tongue(&self.ctx, turns, (x, y), cell, TONGUE.at(elapsed));
+                }
+            }
+        }
+        self.draw_hint()?;
+        self.particles.draw(&self.ctx, now, cell);
+        self.ctx.restore();
+        self.draw_overlays(now, &theme)
+    }
+
+    // Points an arrow from the player's head the way the planner would go,
+    // while the hints are on and the player is steering.
+    fn draw_hint(&self) -> Result<(), JsValue> {
+        let steering = matches!(self.state, AppState::Countdown(_) | AppState::Playing)
+            && self.plugged()
+            && self.playback.is_none();
+        let Some(snake) = self.board.snakes.get(self.local).filter(|s| s.alive) else {
+            return Ok(());
+        };
+        if !steering || snake.autopilot || !settings::with(|s| s.hints) {
+            return Ok(());
+        }
+        let Some(food) = self.board.nearest_food(self.local) else {
+            return Ok(());
+        };
+        let Some(dir) = path::next(&self.board, self.local, food) else {
+            return Ok(());
+        };
+        let Some(head) = snake.body.front() else {
+            return Ok(());
+        };
+        let (x, y) = self.board.rules.wrap_pos((head.0 + dir.0, head.1 + dir.1));
+        let cell = self.cell;
+        sprites::arrow(
+            &self.ctx,
+            (x as f64 * cell, y as f64 * cell),
+            cell,
+            dir,
+            HINT_COLOUR,
+        )
+    }
+
+    // Draws everything over the board: the HUD, banners, the countdown and
+    // the end-of-game tables.
+    fn draw_overlays(&self, now: f64, theme: &Theme) -> Result<(), JsValue> {
+        let cell = self.cell;
+        self.draw_hud()?;
+        let banner = match self.state {
+            AppState::Paused(Pause::Rotate) => Some("Rotate your device to continue".to_string()),
+            AppState::Paused(Pause::Idle | Pause::Hidden) => {
+                Some("Paused \u{2014} press any key".to_string())
+            }
+            AppState::Paused(Pause::Handover) => {
+                let name = self.tournament.as_ref().and_then(Tournament::current);
+                Some(format!(
+                    "{}'s turn \u{2014} press any key",
+                    name.unwrap_or("Next")
+                ))
+            }
+            AppState::Paused(Pause::Perk) => Some("Level up! Pick a perk".to_string()),
+            _ if self.attract => Some("Press any key to play".to_string()),
+            AppState::Menu => Some("Press any key to start".to_string()),
+            AppState::GameOver if self.lockstep.is_some() => {
+                let me = self.board.snakes[self.local].alive;
+                let rival = self
+                    .board
+                    .snakes
+                    .iter()
+                    .enumerate()
+                    .all(|(i, s)| i == self.local || s.alive);
+                Some(
+                    match (me, rival) {
+                        (true, false) => "You win!",
+                        (false, true) => "You lose",
+                        _ => "Draw",
+                    }
+                    .to_string(),
+                )
+            }
+            AppState::GameOver if self.royale.is_some() => {
+                let (alive, _) = Royale::standing(&self.board);
+                Some(if self.board.snakes[self.local].alive {
+                    "Last snake standing!".to_string()
+                } else if alive == 0 {
+                    "Draw".to_string()
+                } else {
+                    format!("Out with {alive} left")
+                })
+            }
+            _ => None,
+        };
+        if let Some(text) = banner {
+            self.ctx.set_fill_style_str(&theme.ink);
+            self.ctx.set_font(&theme.font(16.0));
+            self.ctx.set_text_align("center");
+            self.ctx
+                .fill_text(&text, WIDTH as f64 * cell / 2.0, HEIGHT as f64 * cell / 2.0)?;
+        }
+        if let AppState::Countdown(until) = self.state {
+            let left = ((until - now) / 1000.0).ceil().max(1.0);
+            self.ctx.set_fill_style_str(&theme.ink);
+            self.ctx
+                .set_font(&format!("bold {}", theme.font((cell * 4.0).round())));
+            self.ctx.set_text_align("center");
+            self.ctx.set_text_baseline("middle");
+            self.ctx.fill_text(
+                &left.to_string(),
+                WIDTH as f64 * cell / 2.0,
+                HEIGHT as f64 * cell / 2.0,
+            )?;
+            self.ctx.set_text_baseline("alphabetic");
+        }
+        if self.state == AppState::Paused(Pause::Perk) {
+            self.draw_perks()?;
+        }
+        let over = self.state == AppState::GameOver;
+        if let Some(t) = self.tournament.as_ref().filter(|_| over) {
+            self.draw_standings(t)?;
+        } else if over && self.lockstep.is_none() && !self.attract && !kiosk::enabled() {
+            self.draw_high_scores()?;
+            self.draw_run_stats()?;
+        }
+        if kiosk::enabled() && (self.attract || self.board.over) {
+            if let Some(line) = kiosk::ticker(self.high_scores.entries(), now) {
+                self.ctx.set_fill_style_str("gold");
+                self.ctx.set_font(&theme.font(14.0));
+                self.ctx.set_text_align("center");
+                self.ctx.fill_text(&line, WIDTH as f64 * cell / 2.0, 20.0)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Fills a cell for everything on `board` except the snake `skip`,
+    // which the caller draws itself, with the portals swirled as at `now`.
+    fn draw_entities(
+        &self,
+        ctx: &CanvasRenderingContext2d,
+        board: &Board,
+        skip: &[usize],
+        theme: &Theme,
+        now: f64,
+    ) {
+        let cell = self.cell;
+        let palette = palette::current();
+        // the guides draw a grid on themes without one, and pick out the
+        // row and column the player's head is in
+        let guides = settings::with(|s| s.guides);
+        let grid = match &theme.grid {
+            Some(grid) => Some(grid.as_str()),
+            None => guides.then_some(GUIDE_COLOUR),
+        };
+        if let Some(grid) = grid {
+            ctx.set_fill_style_str(grid);
+            for x in 1..WIDTH {
+                ctx.fill_rect(x as f64 * cell, 0.0, 1.0, HEIGHT as f64 * cell);
+            }
+            for y in 1..HEIGHT {
+                ctx.fill_rect(0.0, y as f64 * cell, WIDTH as f64 * cell, 1.0);
+            }
+        }
+        let head = board.snakes.get(self.local).and_then(|s| s.body.front());
+        if let Some(&(x, y)) = head.filter(|_| guides) {
+            ctx.set_fill_style_str(GUIDE_COLOUR);
+            ctx.fill_rect(x as f64 * cell, 0.0, cell, HEIGHT as f64 * cell);
+            ctx.fill_rect(0.0, y as f64 * cell, WIDTH as f64 * cell, cell);
+        }
+        // the local snake and the food come from the sprite sheet once it
+        // loads; other snakes stay in their palette colour to stand apart
+        let sheet = sprites::sheet();
+        // segments come head first, so this counts along each snake
+        let mut along = 0;
+        for Entity { pos: (x, y), kind } in board.entities() {
+            let (px, py) = (x as f64 * cell, y as f64 * cell);
+            ctx.set_fill_style_str(palette.of(kind, self.local));
+            match kind {
+                Kind::Segment { snake, .. } if skip.contains(&snake) => continue,
+                Kind::Segment { snake, head } => {
+                    along = if head { 0 } else { along + 1 };
+                    let at = (px, py, cell);
+                    self.draw_segment(ctx, board, (snake, along), at, theme, sheet.as_ref());
+                }
+                Kind::Food if sheet.is_some() => {
+                    if let Some(sheet) = &sheet {
+                        let _ = sprites::draw(ctx, sheet, sprites::Tile::Food, 0, (px, py), cell);
+                    }
+                }
+                Kind::Food if !theme.food.is_empty() => {
+                    ctx.set_font(&theme.font(cell));
+                    ctx.set_text_align("center");
+                    ctx.set_text_baseline("middle");
+                    let _ = ctx.fill_text(&theme.food, px + cell / 2.0, py + cell / 2.0);
+                    ctx.set_text_baseline("alphabetic");
+                }
+                Kind::Portal => {
+                    let colour = palette.of(kind, self.local);
+                    let spin = now % PORTAL_SPIN_MS / PORTAL_SPIN_MS;
+                    let _ = sprites::portal(ctx, (px, py), cell, colour, spin);
+                }
+                _ => ctx.fill_rect(px, py, cell, cell),
+            }
+        }
+    }
+
+    // The board as flat quads for a renderer, in the order the 2D canvas
+    // would draw it: the grid and guides, the ghost, what is on the board,
+    // then the gliding snakes.
+    fn quads<'a>(&self, glides: &[(usize, Vec<(f64, f64)>)], theme: &'a Theme) -> Vec<Quad<'a>> {
+        let cell = self.cell;
+        let (w, h) = (WIDTH as f64 * cell, HEIGHT as f64 * cell);
+        let at = |x: f64, y: f64| (x * cell, y * cell, cell, cell);
+        let palette = palette::current();
+        let mut quads = Vec::new();
+        let guides = settings::with(|s| s.guides);
+        let grid = match &theme.grid {
+            Some(grid) => Some(grid.as_str()),
+            None => guides.then_some(GUIDE_COLOUR),
+        };
+        if let Some(colour) = grid {
+            let lines = (1..WIDTH).map(|x| (x as f64 * cell, 0.0, 1.0, h));
+            let lines = lines.chain((1..HEIGHT).map(|y| (0.0, y as f64 * cell, w, 1.0)));
+            quads.extend(lines.map(|rect| Quad {
+                rect,
+                colour,
+                alpha: 1.0,
+            }));
+        }
+        let head = self
+            .board
+            .snakes
+            .get(self.local)
+            .and_then(|s| s.body.front());
+        if let Some(&(x, y)) = head.filter(|_| guides) {
+            for rect in [
+                (x as f64 * cell, 0.0, cell, h),
+                (0.0, y as f64 * cell, w, cell),
+            ] {
+                quads.push(Quad {
+                    rect,
+                    colour: GUIDE_COLOUR,
+                    alpha: 1.0,
+                });
+            }
+        }
+        if let Some(ghost) = &self.ghost {
+            quads.extend(ghost.snake().body.iter().map(|&(x, y)| Quad {
+                rect: at(f64::from(x), f64::from(y)),
+                colour: &theme.ink,
+                alpha: 0.3,
+            }));
+        }
+        let fade = |snake: usize, along: usize| {
+            1.0 - theme.fade * along as f64 / self.board.snakes[snake].body.len() as f64
+        };
+        let mut along = 0;
+        for Entity { pos: (x, y), kind } in self.board.entities() {
+            let alpha = match kind {
+                Kind::Segment { snake, .. } if glides.iter().any(|(i, _)| *i == snake) => continue,
+                Kind::Segment { snake, head } => {
+                    along = if head { 0 } else { along + 1 };
+                    fade(snake, along)
+                }
+                _ => 1.0,
+            };
+            quads.push(Quad {
+                rect: at(f64::from(x), f64::from(y)),
+                colour: palette.of(kind, self.local),
+                alpha,
+            });
+        }
+        for (snake, segments) in glides {
+            let shown = self.dissolving(*snake).unwrap_or(usize::MAX);
+            for (along, &(x, y)) in segments.iter().enumerate().take(shown) {
+                let kind = Kind::Segment {
+                    snake: *snake,
+                    head: along == 0,
+                };
+                quads.push(Quad {
+                    rect: at(x, y),
+                    colour: palette.of(kind, self.local),
+                    alpha: fade(*snake, along),
+                });
+            }
+        }
+        quads
+    }
+
+    // Whether the start screen is waiting on the sprite sheet or fonts.
+    fn loading(&self) -> bool {
+        self.state == AppState::Menu && assets::progress().is_some()
+    }
+
+    // Whether the board is standing still on a menu or a pause, where the
+    // snakes get idle animations.
+    fn idle(&self) -> bool {
+        !self.attract
+            && matches!(
+                self.state,
+                AppState::Menu
+                    | AppState::Paused(Pause::Idle | Pause::Handover | Pause::Perk | Pause::Hidden)
+            )
+    }
+
+    // How many of a crashed snake's segments are left, counting from the
+    // head: it flashes, then goes from the tail up, and stays gone on the
+    // game-over screen. `None` for a snake that is drawn whole.
+    fn dissolving(&self, snake: usize) -> Option<usize> {
+        let s = &self.board.snakes[snake];
+        if s.alive {
+            return None;
+        }
+        let len = s.body.len();
+        match self.state {
+            AppState::Dying { frames_left } if frames_left > DISSOLVE_FRAMES => {
+                Some(if frames_left % 2 == 0 { 0 } else { len })
+            }
+            AppState::Dying { frames_left } => {
+                Some((len * frames_left as usize).div_ceil(DISSOLVE_FRAMES as usize))
+            }
+            AppState::GameOver => Some(0),
+            _ => None,
+        }
+    }
+
+    // Draws segment `along` of `snake`, counting from its head, with its
+    // top left corner at `(x, y)` in pixels and `cell` pixels across.
+    fn draw_segment(
+        &self,
+        ctx: &CanvasRenderingContext2d,
+        board: &Board,
+        (snake, along): (usize, usize),
+        (x, y, cell): (f64, f64, f64),
+        theme: &Theme,
+        sheet: Option<&HtmlImageElement>,
+    ) {
+        let at = (x, y);
+        let s = &board.snakes[snake];
+        ctx.set_global_alpha(1.0 - theme.fade * along as f64 / s.body.len() as f64);
+        let (tile, turns) = sprites::segment(&s.body, along, s.dir);
+        match sheet {
+            Some(sheet) if snake == self.local => {
+                let _ = sprites::draw(ctx, sheet, tile, turns, at, cell);
+            }
+            _ => {
+                let kind = Kind::Segment {
+                    snake,
+                    head: along == 0,
+                };
+                let colour = palette::current().of(kind, self.local);
+                let _ = sprites::draw_flat(ctx, tile, turns, at, cell, colour);
+            }
+        }
+        ctx.set_global_alpha(1.0);
+    }
+
+    // Draws the board as it was at the run's best moment on a canvas of
+    // its own, captioned, and returns it as a PNG data URL.
+    fn render_moment(&self) -> Result<Option<String>, JsValue> {
+        let Some(moment) = self.run.best_moment else {
+            return Ok(None);
+        };
+        let document = web_sys::window()
+            .and_then(|w| w.document())
+            .ok_or("no document")?;
+        let canvas: HtmlCanvasElement = document.create_element("canvas")?.dyn_into()?;
+        let (w, h) = (WIDTH as f64 * self.cell, HEIGHT as f64 * self.cell);
+        canvas.set_width(w as u32);
+        canvas.set_height(h as u32);
+        let ctx = canvas
+            .get_context("2d")?
+            .ok_or("no 2d context")?
+            .dyn_into::<CanvasRenderingContext2d>()?;
+        let theme = theme::current();
+        ctx.set_fill_style_str(&theme.background);
+        ctx.fill_rect(0.0, 0.0, w, h);
+        let past = Board::replay(
+            vec![solo_snake()],
+            self.board.rules,
+            &self.log[..self.cut(moment.tick)],
+        );
+        self.draw_entities(&ctx, &past, &[], &theme, 0.0);
+        ctx.set_fill_style_str(&theme.ink);
+        ctx.set_text_align("center");
+        ctx.set_font(&format!("bold {}", theme.font(16.0)));
+        ctx.fill_text(&moment.highlight.caption(), w / 2.0, 24.0)?;
+        ctx.set_font(&theme.font(14.0));
+        let score = format!(
+            "Score {} at tick {} \u{b7} final {}",
+            format::score(past.snakes[0].score),
+            moment.tick,
+            format::score(self.board.snakes[0].score)
+        );
+        ctx.fill_text(&score, w / 2.0, 44.0)?;
+        Ok(Some(canvas.to_data_url()?))
+    }
+
+    // Downloads the best moment's picture.
+    fn save_moment(&self) -> Result<(), JsValue> {
+        match &self.moment {
+            Some(url) => download(url, "snake-best-moment.png"),
+            None => Ok(()),
+        }
+    }
+
+    // The canvas as it stands, at its full resolution, over a banner with
+    // the score and the date, as a PNG data URL.
+    fn screenshot(&self) -> Result<String, JsValue> {
+        let source = self.ctx.canvas().ok_or("no canvas")?;
+        let document = web_sys::window()
+            .and_then(|w| w.document())
+            .ok_or("no document")?;
+        let canvas: HtmlCanvasElement = document.create_element("canvas")?.dyn_into()?;
+        let (w, h) = (WIDTH as f64 * self.cell, HEIGHT as f64 * self.cell);
+        canvas.set_width(source.width());
+        canvas.set_height(((h + SHOT_BANNER) * self.scale).round() as u32);
+        let ctx = canvas
+            .get_context("2d")?
+            .ok_or("no 2d context")?
+            .dyn_into::<CanvasRenderingContext2d>()?;
+        ctx.scale(self.scale, self.scale)?;
+        ctx.draw_image_with_html_canvas_element_and_dw_and_dh(&source, 0.0, 0.0, w, h)?;
+        let theme = theme::current();
+        ctx.set_fill_style_str(&theme.background);
+        ctx.fill_rect(0.0, h, w, SHOT_BANNER);
+        ctx.set_fill_style_str(&theme.ink);
+        ctx.set_font(&theme.font(16.0));
+        ctx.set_text_baseline("middle");
+        let y = h + SHOT_BANNER / 2.0;
+        let score = self.board.snakes.get(self.local).map_or(0, |s| s.score);
+        ctx.set_text_align("left");
+        ctx.fill_text(&format!("Score {}", format::score(score)), 10.0, y)?;
+        ctx.set_text_align("right");
+        ctx.fill_text(&format::date(js_sys::Date::now()), w - 10.0, y)?;
+        canvas.to_data_url()
+    }
+
+    fn draw_perks(&self) -> Result<(), JsValue> {
+        let x = WIDTH as f64 * self.cell / 2.0;
+        let mut y = HEIGHT as f64 * self.cell / 2.0;
+        let perks = self.board.snakes[self.local].perks;
+        let theme = theme::current();
+        self.ctx.set_text_align("center");
+        self.ctx.set_font(&theme.font(14.0));
+        self.ctx.set_fill_style_str(&theme.ink);
+        for (i, perk) in PERKS.into_iter().enumerate() {
+            y += 20.0;
+            let owned = perks.count(perk);
+            let text = if owned > 0 {
+                format!("{}. {} (have {owned})", i + 1, perk.label())
+            } else {
+                format!("{}. {}", i + 1, perk.label())
+            };
+            self.ctx.fill_text(&text, x, y)?;
+        }
+        Ok(())
+    }
+
+    fn draw_standings(&self, t: &Tournament) -> Result<(), JsValue> {
+        let x = WIDTH as f64 * self.cell / 2.0;
+        let mut y = 40.0;
+        let theme = theme::current();
+        self.ctx.set_text_align("center");
+        self.ctx.set_fill_style_str("gold");
+        self.ctx.set_font(&theme.font(18.0));
         self.ctx
-            .fill_rect(0.0, 0.0, WIDTH as f64 * CELL, HEIGHT as f64 * CELL);
-        self.ctx.set_fill_style(&JsValue::from_str("green"));
-        for (x, y) in self.snake.iter() {
+            .fill_text(if t.finished() { "Podium" } else { "Standings" }, x, y)?;
+        self.ctx.set_font(&theme.font(14.0));
+        for (rank, (name, score, handicap)) in t.standings().into_iter().enumerate() {
+            y += 20.0;
+            let podium = t.finished() && rank < 3;
             self.ctx
-                .fill_rect(*x as f64 * CELL, *y as f64 * CELL, CELL, CELL);
-        }
-        self.ctx.set_fill_style(&JsValue::from_str("red"));
-        self.ctx.fill_rect(
-            self.food.0 as f64 * CELL,
-            self.food.1 as f64 * CELL,
-            CELL,
-            CELL,
+                .set_fill_style_str(if podium { "gold" } else { &theme.ink });
+            self.ctx.fill_text(
+                &format!(
+                    "{}. {name} \u{2014} {}{}",
+                    rank + 1,
+                    format::score(score),
+                    handicap.label()
+                ),
+                x,
+                y,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn draw_high_scores(&self) -> Result<(), JsValue> {
+        let x = WIDTH as f64 * self.cell / 2.0;
+        let mut y = 40.0;
+        let theme = theme::current();
+        self.ctx.set_text_align("center");
+        self.ctx.set_fill_style_str("gold");
+        self.ctx.set_font(&theme.font(18.0));
+        self.ctx.fill_text("High scores", x, y)?;
+        self.ctx.set_font(&theme.font(14.0));
+        for (rank, entry) in self.high_scores.entries().iter().enumerate() {
+            y += 20.0;
+            let fresh = self.new_rank == Some(rank);
+            self.ctx
+                .set_fill_style_str(if fresh { "gold" } else { &theme.ink });
+            let line = format!(
+                "{}. {} {} \u{2014} {}",
+                rank + 1,
+                entry.initials.as_deref().unwrap_or("---"),
+                format::score(entry.score),
+                entry.date
+            );
+            self.ctx.fill_text(&line, x, y)?;
+        }
+        Ok(())
+    }
+
+    // The finished run's figures along the bottom of the board, under the
+    // high scores.
+    fn draw_run_stats(&self) -> Result<(), JsValue> {
+        let x = WIDTH as f64 * self.cell / 2.0;
+        let bottom = HEIGHT as f64 * self.cell - 10.0;
+        let score = self.board.snakes[self.local].score;
+        let lines = self.run.lines(score);
+        let theme = theme::current();
+        self.ctx.set_text_align("center");
+        self.ctx.set_font(&theme.font(14.0));
+        self.ctx.set_fill_style_str(&theme.ink);
+        let top = bottom - 18.0 * (lines.len() + 1) as f64;
+        for (i, line) in lines.iter().enumerate() {
+            self.ctx.fill_text(line, x, top + 18.0 * i as f64)?;
+        }
+        self.ctx.set_fill_style_str("gold");
+        self.ctx.fill_text(
+            "Enter to play again \u{b7} Esc for the menu",
+            x,
+            bottom - 18.0,
+        )?;
+        let copy = if self.moment.is_some() {
+            "C to copy today's games \u{b7} S to save the best moment"
+        } else {
+            "C to copy today's games"
+        };
+        self.ctx.fill_text(copy, x, bottom)
+    }
+
+    // The debug overlay's figures: the loop's timings, and the network's
+    // while there is a connection.
+    fn debug_lines(&self) -> Vec<String> {
+        let mut lines = vec![perf::summary()];
+        let Some(stats) = net::stats() else {
+            return lines;
+        };
+        let rtt = stats
+            .rtt_ms
+            .map_or("-".to_string(), |rtt| format!("{rtt:.0}"));
+        lines.extend([
+            format!("rtt {rtt} ms \u{b7} jitter {:.1} ms", stats.jitter_ms),
+            format!("inputs {:.1}/s", stats.inputs_per_sec),
+            format!(
+                "in {:.0} B/s \u{b7} out {:.0} B/s",
+                stats.bytes_in_per_sec, stats.bytes_out_per_sec
+            ),
+            format!("stalls {}", stats.stalls),
+        ]);
+        lines
+    }
+
+    // The run as it stood at the scrubbed tick, a timeline along the bottom
+    // and the local snake's state at that point.
+    fn draw_time_travel(&self) -> Result<(), JsValue> {
+        let cell = self.cell;
+        let (w, h) = (WIDTH as f64 * cell, HEIGHT as f64 * cell);
+        let past = Board::replay(
+            vec![solo_snake()],
+            self.board.rules,
+            &self.log[..self.cut(self.scrub)],
         );
+        self.draw_entities(
+            &self.ctx,
+            &past,
+            &[],
+            &theme::current(),
+            js_sys::Date::now(),
+        );
+        let at = if self.board.tick == 0 {
+            1.0
+        } else {
+            f64::from(self.scrub) / f64::from(self.board.tick)
+        };
+        self.ctx.set_fill_style_str("rgba(128, 128, 128, 0.5)");
+        self.ctx.fill_rect(0.0, h - 8.0, w, 8.0);
+        self.ctx.set_fill_style_str("gold");
+        self.ctx.fill_rect(0.0, h - 8.0, w * at, 8.0);
+        self.ctx.fill_rect(w * at - 2.0, h - 14.0, 4.0, 14.0);
+        let snake = &past.snakes[0];
+        let head = snake.body.front().copied().unwrap_or_default();
+        let food = if past.food.is_empty() {
+            "-".to_string()
+        } else {
+            let cells: Vec<String> = past
+                .food
+                .iter()
+                .map(|(x, y)| format!("({x}, {y})"))
+                .collect();
+            cells.join(" ")
+        };
+        let lines = [
+            format!("tick {}/{}", self.scrub, self.board.tick),
+            format!(
+                "score {} \u{b7} length {}",
+                format::score(snake.score),
+                snake.body.len()
+            ),
+            format!(
+                "head ({}, {}) \u{b7} heading ({}, {})",
+                head.0, head.1, snake.dir.0, snake.dir.1
+            ),
+            format!("food {food}"),
+            "\u{2190}/\u{2192} step \u{b7} Enter branch \u{b7} F4 close".to_string(),
+        ];
+        let theme = theme::current();
+        self.ctx.set_fill_style_str(&theme.ink);
+        self.ctx.set_font(&theme.mono(12.0));
+        self.ctx.set_text_align("left");
+        for (i, line) in lines.iter().enumerate() {
+            self.ctx.fill_text(line, 4.0, 14.0 + 14.0 * i as f64)?;
+        }
         Ok(())
     }
+
+    // What each HUD widget shows this frame; the layout in the settings
+    // says where.
+    fn widget(&self, widget: Widget) -> Option<Content<'_>> {
+        let snake = self.board.snakes.get(self.local)?;
+        match widget {
+            Widget::Score => Some(Content::line(self.hud.clone())),
+            Widget::Speed => {
+                let tick_ms = settings::with(|s| s.tick_ms) as f64;
+                Some(Content::line(format!(
+                    "{:.1} ticks/s",
+                    self.speed() * 1000.0 / tick_ms
+                )))
+            }
+            Widget::Timer => Some(Content::line(self.run.clock())),
+            Widget::Combo => {
+                let combo = self.run.combo(self.board.tick);
+                (combo > 1).then(|| Content::line(format!("combo \u{d7}{combo}")))
+            }
+            Widget::Stamina => (self.boost.stamina() < 1.0).then(|| Content::Meter {
+                fill: self.boost.stamina(),
+            }),
+            Widget::Minimap => Some(Content::Minimap {
+                board: &self.board,
+                local: self.local,
+            }),
+            Widget::Effects => {
+                let mut effects = Vec::new();
+                if snake.autopilot {
+                    effects.push("autopilot".to_string());
+                }
+                if snake.shields > 0 {
+                    effects.push(format!("shield {}", snake.shields));
+                }
+                if self.boost.active() {
+                    effects.push("boost".to_string());
+                }
+                (!effects.is_empty()).then(|| Content::line(effects.join(" \u{b7} ")))
+            }
+            Widget::Stats => settings::with(|s| s.stats_panel).then(|| Content::Text {
+                lines: self.run.live().to_vec(),
+                small: true,
+            }),
+            Widget::Debug => settings::with(|s| s.debug_hud)
+                .then(|| self.debug_lines())
+                .map(|lines| Content::Text { lines, small: true }),
+        }
+    }
+
+    fn draw_hud(&self) -> Result<(), JsValue> {
+        let (w, h) = (WIDTH as f64 * self.cell, HEIGHT as f64 * self.cell);
+        hud::draw(&self.ctx, w, h, |widget| self.widget(widget))
+    }
+
+    // The editor's sliders, and the sample board in the edited theme.
+    fn draw_editor(&self, editor: &ThemeEditor) -> Result<(), JsValue> {
+        let (w, h) = (WIDTH as f64 * self.cell, HEIGHT as f64 * self.cell);
+        let theme = &editor.theme;
+        self.ctx.set_fill_style_str(&theme.background);
+        self.ctx.fill_rect(0.0, 0.0, w, h);
+        editor.draw(&self.ctx, w, h)?;
+        let (x, y, size) = ThemeEditor::preview(w, h);
+        self.ctx.save();
+        self.ctx.translate(x, y)?;
+        self.ctx.scale(size / w, size / h)?;
+        self.draw_entities(
+            &self.ctx,
+            &editor::sample(),
+            &[],
+            theme,
+            js_sys::Date::now(),
+        );
+        self.ctx.restore();
+        self.ctx.set_stroke_style_str(&theme.ink);
+        self.ctx.stroke_rect(x, y, size, size);
+        Ok(())
+    }
+
+    fn draw_playback(&self, pb: &Playback) -> Result<(), JsValue> {
+        let cell = self.cell;
+        self.draw_entities(
+            &self.ctx,
+            &pb.ghost.board,
+            &[],
+            &theme::current(),
+            js_sys::Date::now(),
+        );
+        let mut status = format!(
+            "{} {}/{} \u{b7} {}x \u{b7} score {}",
+            self.hud,
+            pb.ghost.tick(),
+            pb.ticks(),
+            pb.speed(),
+            format::score(pb.ghost.score())
+        );
+        if pb.ghost.board.snakes.iter().any(|s| s.autopilot) {
+            status.push_str(" \u{b7} autopilot");
+        }
+        let theme = theme::current();
+        self.ctx.set_fill_style_str(&theme.ink);
+        self.ctx.set_font(&theme.font(14.0));
+        self.ctx.set_text_align("center");
+        self.ctx.fill_text(&status, WIDTH as f64 * cell / 2.0, 20.0)
+    }
 }
@@ -0,0 +1,254 @@
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::board::{self, Rules};
+use crate::core::handicap::Handicap;
+use crate::schema;
+use crate::transport::Transport;
+
+const PING_MS: f64 = 1000.0;
+// window over which the per-second rates are measured
+const WINDOW_MS: f64 = 1000.0;
+
+#[derive(Serialize, Deserialize)]
+pub enum Msg {
+    /// The connection opened; never sent over the wire.
+    Open,
+    /// The connection closed; never sent over the wire.
+    Closed,
+    /// Starts a round: the seed, the host's and guest's handicaps and the
+    /// host's rules.
+    Seed(u64, [Handicap; 2], Rules),
+    Input(u32, (i32, i32)),
+    Rematch,
+    /// The sender's initials or guest name, sent when the connection opens.
+    Name(String),
+    /// Round-trip probes, answered by the transport itself.
+    Ping(f64),
+    Pong(f64),
+}
+
+impl Msg {
+    fn encode(&self) -> Option<String> {
+        match self {
+            Msg::Open | Msg::Closed => None,
+            msg => schema::to_json(msg),
+        }
+    }
+
+    fn decode(text: &str) -> Option<Msg> {
+        match schema::from_json(text)? {
+            Msg::Open | Msg::Closed => None,
+            Msg::Seed(_, handicaps, _) if !handicaps.iter().all(Handicap::valid) => None,
+            Msg::Seed(_, _, rules) if !rules.valid() => None,
+            msg => Some(msg),
+        }
+    }
+}
+
+/// Link measurements for the debug overlay.
+#[derive(Clone, Copy, Default)]
+pub struct Stats {
+    pub rtt_ms: Option<f64>,
+    /// Smoothed variation between consecutive round trips.
+    pub jitter_ms: f64,
+    pub inputs_per_sec: f64,
+    pub bytes_in_per_sec: f64,
+    pub bytes_out_per_sec: f64,
+    /// Ticks the game waited for the rival's input.
+    pub stalls: u32,
+}
+
+struct Meter {
+    stats: Stats,
+    window_start: f64,
+    bytes_in: usize,
+    bytes_out: usize,
+    inputs: u32,
+    last_ping: f64,
+}
+
+impl Meter {
+    fn new(now: f64) -> Self {
+        Self {
+            stats: Stats::default(),
+            window_start: now,
+            bytes_in: 0,
+            bytes_out: 0,
+            inputs: 0,
+            last_ping: f64::NEG_INFINITY,
+        }
+    }
+
+    fn roll(&mut self, now: f64) {
+        let elapsed = now - self.window_start;
+        if elapsed < WINDOW_MS {
+            return;
+        }
+        let per_sec = |n: f64| n * 1000.0 / elapsed;
+        self.stats.inputs_per_sec = per_sec(self.inputs as f64);
+        self.stats.bytes_in_per_sec = per_sec(self.bytes_in as f64);
+        self.stats.bytes_out_per_sec = per_sec(self.bytes_out as f64);
+        self.inputs = 0;
+        self.bytes_in = 0;
+        self.bytes_out = 0;
+        self.window_start = now;
+    }
+
+    fn round_trip(&mut self, rtt: f64) {
+        if let Some(last) = self.stats.rtt_ms {
+            // RFC 3550 style running estimate
+            self.stats.jitter_ms += ((rtt - last).abs() - self.stats.jitter_ms) / 16.0;
+        }
+        self.stats.rtt_ms = Some(rtt);
+    }
+}
+
+struct Link {
+    id: u32,
+    transport: Box<dyn Transport>,
+    host: bool,
+}
+
+thread_local! {
+    static LINK: RefCell<Option<Link>> = const { RefCell::new(None) };
+    static LAST_ID: Cell<u32> = const { Cell::new(0) };
+    static INBOX: RefCell<VecDeque<Msg>> = const { RefCell::new(VecDeque::new()) };
+    static METER: RefCell<Meter> = RefCell::new(Meter::new(0.0));
+}
+
+/// Replaces any current connection with the transport `make` builds. The
+/// transport reports its events under the id it is given, so stragglers
+/// from an earlier connection are ignored.
+pub fn connect<T: Transport + 'static>(host: bool, make: impl FnOnce(u32) -> T) {
+    let id = LAST_ID.get() + 1;
+    LAST_ID.set(id);
+    let transport = Box::new(make(id));
+    METER.set(Meter::new(js_sys::Date::now()));
+    let old = LINK.replace(Some(Link {
+        id,
+        transport,
+        host,
+    }));
+    if let Some(old) = old {
+        old.transport.close();
+    }
+}
+
+fn current(id: u32) -> bool {
+    LINK.with(|l| l.borrow().as_ref().is_some_and(|link| link.id == id))
+}
+
+/// Settles which side hosts, for transports that only find out once the
+/// other side answers.
+pub fn set_host(id: u32, host: bool) {
+    LINK.with(|l| {
+        if let Some(link) = l.borrow_mut().as_mut().filter(|link| link.id == id) {
+            link.host = host;
+        }
+    });
+}
+
+/// Drops the current connection, telling the other side where the
+/// transport can.
+pub fn disconnect() {
+    if let Some(link) = LINK.take() {
+        link.transport.close();
+    }
+}
+
+pub fn opened(id: u32) {
+    if current(id) {
+        push(Msg::Open);
+    }
+}
+
+pub fn received(id: u32, frame: String) {
+    if !current(id) {
+        return;
+    }
+    METER.with(|m| m.borrow_mut().bytes_in += frame.len());
+    match Msg::decode(&frame) {
+        Some(Msg::Ping(sent)) => send(&Msg::Pong(sent)),
+        Some(Msg::Pong(sent)) => {
+            let rtt = js_sys::Date::now() - sent;
+            METER.with(|m| m.borrow_mut().round_trip(rtt));
+        }
+        // a heading no key can make would let the rival move diagonally or
+        // jump, so the match ends there
+        Some(Msg::Input(_, dir)) if !board::is_heading(dir) => {
+            disconnect();
+            push(Msg::Closed);
+        }
+        Some(msg) => {
+            if matches!(msg, Msg::Input(..)) {
+                METER.with(|m| m.borrow_mut().inputs += 1);
+            }
+            push(msg);
+        }
+        None => {}
+    }
+}
+
+pub fn closed(id: u32) {
+    if current(id) {
+        LINK.set(None);
+        push(Msg::Closed);
+    }
+}
+
+fn push(msg: Msg) {
+    INBOX.with(|inbox| inbox.borrow_mut().push_back(msg));
+}
+
+pub fn is_host() -> bool {
+    LINK.with(|l| l.borrow().as_ref().is_some_and(|link| link.host))
+}
+
+pub fn send(msg: &Msg) {
+    let Some(frame) = msg.encode() else {
+        return;
+    };
+    let sent = LINK.with(|l| {
+        l.borrow()
+            .as_ref()
+            .is_some_and(|link| link.transport.send(&frame))
+    });
+    if sent {
+        METER.with(|m| m.borrow_mut().bytes_out += frame.len());
+    }
+}
+
+pub fn drain() -> Vec<Msg> {
+    INBOX.with(|inbox| inbox.borrow_mut().drain(..).collect())
+}
+
+/// The current link measurements, while connected to a peer.
+pub fn stats() -> Option<Stats> {
+    LINK.with(|l| l.borrow().is_some())
+        .then(|| METER.with(|m| m.borrow().stats))
+}
+
+/// Rolls the rate window and pings the peer when one is due; called once
+/// per game tick.
+pub fn sample() {
+    let now = js_sys::Date::now();
+    let ping = METER.with(|m| {
+        let mut meter = m.borrow_mut();
+        meter.roll(now);
+        let due = now - meter.last_ping >= PING_MS;
+        if due {
+            meter.last_ping = now;
+        }
+        due
+    });
+    if ping {
+        send(&Msg::Ping(now));
+    }
+}
+
+pub fn note_stall() {
+    METER.with(|m| m.borrow_mut().stats.stalls += 1);
+}
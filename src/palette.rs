@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::core::entity::Kind;
+use crate::settings;
+
+/// Colours for what sits on the board. Everything that colours a snake,
+/// the food or a hazard takes it from here rather than naming a colour.
+#[derive(Clone, Copy)]
+pub struct Palette {
+    /// This browser's snake.
+    pub snake: &'static str,
+    /// Every other snake.
+    pub rival: &'static str,
+    pub food: &'static str,
+    pub poison: &'static str,
+    pub wall: &'static str,
+    pub portal: &'static str,
+}
+
+impl Palette {
+    /// The colour of `kind` as seen by the player of snake `local`.
+    pub fn of(&self, kind: Kind, local: usize) -> &'static str {
+        match kind {
+            Kind::Segment { snake, .. } if snake == local => self.snake,
+            Kind::Segment { .. } => self.rival,
+            Kind::Food => self.food,
+            Kind::Poison => self.poison,
+            Kind::Wall => self.wall,
+            Kind::Portal => self.portal,
+        }
+    }
+}
+
+const STANDARD: Palette = Palette {
+    snake: "green",
+    rival: "deepskyblue",
+    food: "red",
+    poison: "purple",
+    wall: "dimgray",
+    portal: "darkorange",
+};
+
+// The others are picked from the Okabe-Ito set, whose colours stay apart
+// for each kind of colour blindness, and read on both themes.
+const DEUTERANOPIA: Palette = Palette {
+    snake: "#0072b2",
+    rival: "#56b4e9",
+    food: "#e69f00",
+    poison: "#cc79a7",
+    wall: "#999999",
+    portal: "#009e73",
+};
+
+// red looks dark to protanopes, so the food is yellow instead of orange
+const PROTANOPIA: Palette = Palette {
+    snake: "#0072b2",
+    rival: "#56b4e9",
+    food: "#f0e442",
+    poison: "#cc79a7",
+    wall: "#999999",
+    portal: "#009e73",
+};
+
+// blue and green run together for tritanopes, as do yellow and violet
+const TRITANOPIA: Palette = Palette {
+    snake: "#009e73",
+    rival: "#cc79a7",
+    food: "#d55e00",
+    poison: "#882255",
+    wall: "#999999",
+    portal: "#0072b2",
+};
+
+/// Which palette the board is drawn in.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scheme {
+    Standard,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+impl Scheme {
+    pub fn parse(name: &str) -> Option<Scheme> {
+        match name {
+            "standard" => Some(Scheme::Standard),
+            "deuteranopia" => Some(Scheme::Deuteranopia),
+            "protanopia" => Some(Scheme::Protanopia),
+            "tritanopia" => Some(Scheme::Tritanopia),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Scheme::Standard => "standard",
+            Scheme::Deuteranopia => "deuteranopia",
+            Scheme::Protanopia => "protanopia",
+            Scheme::Tritanopia => "tritanopia",
+        }
+    }
+
+    pub fn palette(self) -> Palette {
+        match self {
+            Scheme::Standard => STANDARD,
+            Scheme::Deuteranopia => DEUTERANOPIA,
+            Scheme::Protanopia => PROTANOPIA,
+            Scheme::Tritanopia => TRITANOPIA,
+        }
+    }
+}
+
+/// The palette picked in the settings.
+pub fn current() -> Palette {
+    settings::with(|s| s.palette.palette())
+}
+
+/// `"standard"`, `"deuteranopia"`, `"protanopia"` or `"tritanopia"`.
+#[wasm_bindgen]
+pub fn set_palette(name: &str) -> Result<(), JsValue> {
+    let scheme = Scheme::parse(name).ok_or_else(|| JsValue::from_str("unknown palette"))?;
+    settings::update(|s| s.palette = scheme);
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use std::f64::consts::PI;
+
+use web_sys::CanvasRenderingContext2d;
+
+use crate::core::rng::Rng;
+use crate::quality;
+
+const BURST: usize = 14;
+const LIFE_MS: f64 = 600.0;
+// cells per second
+const SPEED: f64 = 4.0;
+// cells per second squared, pulling the sparks down as they go
+const GRAVITY: f64 = 6.0;
+
+/// One spark, moving in a straight line from where it was born and falling
+/// as it goes. Positions are in cells, so they follow the board's scale.
+struct Particle {
+    origin: (f64, f64),
+    velocity: (f64, f64),
+    born: f64,
+    colour: &'static str,
+}
+
+impl Particle {
+    // Where it is and how opaque, `age` ms after it was born.
+    fn integrate(&self, age: f64) -> ((f64, f64), f64) {
+        let t = age / 1000.0;
+        let x = self.origin.0 + self.velocity.0 * t;
+        let y = self.origin.1 + self.velocity.1 * t + GRAVITY * t * t / 2.0;
+        ((x, y), (1.0 - age / LIFE_MS).max(0.0))
+    }
+}
+
+/// Sparks thrown off as food is eaten. They are drawn from the wall clock
+/// alone and never touch the board, so the game plays the same with or
+/// without them.
+#[derive(Default)]
+pub struct Particles {
+    live: Vec<Particle>,
+    rng: Option<Rng>,
+}
+
+impl Particles {
+    /// Throws a burst of sparks from the middle of cell `at`, unless the
+    /// battery saver or reduced motion is on.
+    pub fn burst(&mut self, at: (i32, i32), colour: &'static str, now: f64) {
+        if still() {
+            return;
+        }
+        // seeded off the clock rather than the game's rng, which the board
+        // depends on
+        let rng = self.rng.get_or_insert_with(|| Rng::new(now.to_bits()));
+        let origin = (f64::from(at.0) + 0.5, f64::from(at.1) + 0.5);
+        for i in 0..BURST {
+            let angle = 2.0 * PI * (i as f64 + f64::from(rng.below(100)) / 100.0) / BURST as f64;
+            let speed = SPEED * (0.5 + f64::from(rng.below(100)) / 100.0);
+            self.live.push(Particle {
+                origin,
+                velocity: (angle.cos() * speed, angle.sin() * speed - SPEED / 2.0),
+                born: now,
+                colour,
+            });
+        }
+    }
+
+    /// Drops the sparks that have faded out by `now`.
+    pub fn prune(&mut self, now: f64) {
+        self.live.retain(|p| now - p.born < LIFE_MS);
+    }
+
+    pub fn draw(&self, ctx: &CanvasRenderingContext2d, now: f64, cell: f64) {
+        // sparks already flying stop when either is switched on
+        if still() {
+            return;
+        }
+        let size = (cell / 4.0).max(2.0);
+        for p in &self.live {
+            let ((x, y), alpha) = p.integrate(now - p.born);
+            if alpha <= 0.0 {
+                continue;
+            }
+            ctx.set_global_alpha(alpha);
+            ctx.set_fill_style_str(p.colour);
+            ctx.fill_rect(x * cell - size / 2.0, y * cell - size / 2.0, size, size);
+        }
+        ctx.set_global_alpha(1.0);
+    }
+}
+
+// Whether effects should keep still, to save the battery or for players
+// who asked for less motion.
+fn still() -> bool {
+    quality::low_power() || quality::reduced_motion()
+}
@@ -0,0 +1,156 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use web_sys::Performance;
+
+// how many of the latest runs the averages cover
+const WINDOW: usize = 120;
+
+thread_local! {
+    static TIMINGS: RefCell<Timings> = RefCell::new(Timings::default());
+}
+
+#[derive(Default)]
+struct Timings {
+    update: Series,
+    draw: Series,
+}
+
+// The latest durations of one part of the loop, and how many ran over.
+#[derive(Default)]
+struct Series {
+    ms: VecDeque<f64>,
+    over: u32,
+}
+
+impl Series {
+    fn push(&mut self, ms: f64, budget_ms: f64) {
+        if self.ms.len() == WINDOW {
+            self.ms.pop_front();
+        }
+        self.ms.push_back(ms);
+        if ms > budget_ms {
+            self.over += 1;
+        }
+    }
+
+    fn stats(&self, budget_ms: f64) -> Stats {
+        let total: f64 = self.ms.iter().sum();
+        Stats {
+            avg_ms: total / self.ms.len().max(1) as f64,
+            max_ms: self.ms.iter().copied().fold(0.0, f64::max),
+            budget_ms,
+            over_budget: self.over,
+        }
+    }
+}
+
+/// The part of the loop being timed.
+#[derive(Clone, Copy)]
+pub enum Part {
+    /// A step of the game clock.
+    Update,
+    /// A frame.
+    Draw,
+}
+
+impl Part {
+    fn name(self) -> &'static str {
+        match self {
+            Part::Update => "snake-update",
+            Part::Draw => "snake-draw",
+        }
+    }
+}
+
+/// A part of the loop under way, marked on the browser's performance
+/// timeline so it shows up in the profiler.
+pub struct Span {
+    part: Part,
+    performance: Option<Performance>,
+    start: f64,
+}
+
+/// Starts timing `part`.
+pub fn begin(part: Part) -> Span {
+    let performance = web_sys::window().and_then(|w| w.performance());
+    let start = performance.as_ref().map_or(0.0, |p| {
+        let _ = p.mark(&format!("{}-start", part.name()));
+        p.now()
+    });
+    Span {
+        part,
+        performance,
+        start,
+    }
+}
+
+impl Span {
+    /// Stops the clock and counts the run against `budget_ms`.
+    pub fn end(self, budget_ms: f64) {
+        let Some(p) = self.performance else {
+            return;
+        };
+        let ms = p.now() - self.start;
+        let name = self.part.name();
+        let start = format!("{name}-start");
+        let end = format!("{name}-end");
+        let _ = p.mark(&end);
+        let _ = p.measure_with_start_mark_and_end_mark(name, &start, &end);
+        // the profiler has them by now; the buffer would only grow
+        p.clear_marks_with_mark_name(&start);
+        p.clear_marks_with_mark_name(&end);
+        p.clear_measures_with_measure_name(name);
+        TIMINGS.with(|t| {
+            let mut t = t.borrow_mut();
+            match self.part {
+                Part::Update => t.update.push(ms, budget_ms),
+                Part::Draw => t.draw.push(ms, budget_ms),
+            }
+        });
+    }
+}
+
+#[derive(Serialize)]
+struct Stats {
+    avg_ms: f64,
+    max_ms: f64,
+    budget_ms: f64,
+    over_budget: u32,
+}
+
+/// Averages over the latest clock steps and frames, for the debug overlay.
+pub fn summary() -> String {
+    TIMINGS.with(|t| {
+        let t = t.borrow();
+        let avg = |s: &Series| s.stats(0.0).avg_ms;
+        format!(
+            "update {:.2} ms \u{b7} draw {:.2} ms \u{b7} slow {}",
+            avg(&t.update),
+            avg(&t.draw),
+            t.update.over + t.draw.over
+        )
+    })
+}
+
+/// How long the game clock's steps and the frames have been taking, as
+/// JSON `{update, draw}`, each with the `avg_ms` and `max_ms` over the
+/// latest 120, the `budget_ms` they have (the tick and the frame interval)
+/// and how many ran `over_budget` since the page loaded.
+#[wasm_bindgen]
+pub fn get_perf_stats() -> String {
+    let budgets = (
+        f64::from(crate::settings::with(|s| s.tick_ms)),
+        crate::quality::frame_ms(),
+    );
+    TIMINGS.with(|t| {
+        let t = t.borrow();
+        serde_json::json!({
+            "update": t.update.stats(budgets.0),
+            "draw": t.draw.stats(budgets.1),
+        })
+        .to_string()
+    })
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::{settings, storage, with_game};
+
+const DAY_MS: f64 = 24.0 * 60.0 * 60.0 * 1000.0;
+
+/// Kinds of the player's data the game can keep or send, each of which
+/// they can turn off.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Data {
+    /// A run in progress, saved with its replay when the tab closes.
+    Replays,
+    /// The high-score table and today's session summary.
+    Stats,
+    /// Scores and replays sent to an online leaderboard.
+    Telemetry,
+}
+
+/// What the player agrees to the game keeping, and for how long. Settings
+/// themselves are always kept, since this lives among them.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Privacy {
+    pub replays: bool,
+    pub stats: bool,
+    pub telemetry: bool,
+    /// Days replays and stats are kept after they were last written;
+    /// `None` keeps them until they are deleted.
+    pub keep_days: Option<u32>,
+}
+
+impl Default for Privacy {
+    fn default() -> Self {
+        Self {
+            replays: true,
+            stats: true,
+            telemetry: true,
+            keep_days: None,
+        }
+    }
+}
+
+impl Privacy {
+    /// Whether `data` may be kept or sent at all.
+    pub fn allows(&self, data: Data) -> bool {
+        match data {
+            Data::Replays => self.replays,
+            Data::Stats => self.stats,
+            Data::Telemetry => self.telemetry,
+        }
+    }
+
+    /// Whether something written at `saved` (in ms since the epoch) is
+    /// still within the retention period at `now`.
+    pub fn fresh(&self, saved: f64, now: f64) -> bool {
+        self.keep_days
+            .is_none_or(|days| now - saved < f64::from(days) * DAY_MS)
+    }
+}
+
+/// Whether the player allows `data`.
+pub fn allows(data: Data) -> bool {
+    settings::with(|s| s.privacy.allows(data))
+}
+
+/// Lets the game keep or send `what` (`"replays"`, `"stats"` or
+/// `"telemetry"`), or not. Turning one off deletes what is already kept.
+#[wasm_bindgen]
+pub fn set_privacy(what: &str, on: bool) -> Result<(), JsValue> {
+    let mut result = Ok(());
+    settings::update(|s| match what {
+        "replays" => s.privacy.replays = on,
+        "stats" => s.privacy.stats = on,
+        "telemetry" => s.privacy.telemetry = on,
+        _ => result = Err(JsValue::from_str("unknown data")),
+    });
+    storage::purge();
+    result
+}
+
+/// Keeps replays and stats for `days` after they were last written, or
+/// for as long as the player likes with `undefined`. Anything older goes
+/// straight away.
+#[wasm_bindgen]
+pub fn set_retention(days: Option<u32>) {
+    settings::update(|s| s.privacy.keep_days = days);
+    storage::purge();
+}
+
+/// Deletes everything the game has stored on this device, settings
+/// included, and starts the page's session over from the defaults.
+#[wasm_bindgen]
+pub fn delete_my_data() {
+    storage::wipe();
+    settings::load();
+    with_game(|g| g.forget());
+}
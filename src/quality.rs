@@ -0,0 +1,71 @@
+use std::cell::Cell;
+
+use js_sys::{Function, Promise, Reflect};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{BatteryManager, Navigator};
+
+use crate::settings;
+
+const FULL_FPS: f64 = 60.0;
+const SAVER_FPS: f64 = 30.0;
+// battery level below which an unplugged device switches to battery saver
+const LOW_BATTERY: f64 = 0.2;
+
+thread_local! {
+    static BATTERY_LOW: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Forces battery saver on or off; `undefined` returns to following the
+/// battery level.
+#[wasm_bindgen]
+pub fn set_battery_saver(on: Option<bool>) {
+    settings::update(|s| s.battery_saver = on);
+}
+
+pub fn low_power() -> bool {
+    settings::with(|s| s.battery_saver).unwrap_or(BATTERY_LOW.get())
+}
+
+/// Whether the system asks for as little animation as possible.
+pub fn reduced_motion() -> bool {
+    web_sys::window()
+        .and_then(|w| w.match_media("(prefers-reduced-motion: reduce)").ok())
+        .flatten()
+        .is_some_and(|query| query.matches())
+}
+
+/// Minimum time between rendered frames.
+pub fn frame_ms() -> f64 {
+    1000.0 / if low_power() { SAVER_FPS } else { FULL_FPS }
+}
+
+/// Follows the Battery Status API where the browser offers it.
+pub fn watch_battery(navigator: &Navigator) {
+    let Ok(get_battery) = Reflect::get(navigator, &JsValue::from_str("getBattery")) else {
+        return;
+    };
+    let Some(get_battery) = get_battery.dyn_ref::<Function>() else {
+        return;
+    };
+    let Ok(promise) = get_battery.call0(navigator) else {
+        return;
+    };
+    spawn_local(async move {
+        let Ok(battery) = JsFuture::from(Promise::from(promise)).await else {
+            return;
+        };
+        let battery: BatteryManager = battery.unchecked_into();
+        sample(&battery);
+        let watched = battery.clone();
+        let closure = Closure::wrap(Box::new(move || sample(&watched)) as Box<dyn FnMut()>);
+        battery.set_onlevelchange(Some(closure.as_ref().unchecked_ref()));
+        battery.set_onchargingchange(Some(closure.as_ref().unchecked_ref()));
+        closure.forget();
+    });
+}
+
+fn sample(battery: &BatteryManager) {
+    BATTERY_LOW.set(!battery.charging() && battery.level() <= LOW_BATTERY);
+}
@@ -0,0 +1,101 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use web_sys::{
+    Blob, BlobEvent, BlobPropertyBag, HtmlCanvasElement, MediaRecorder, MediaRecorderOptions, Url,
+};
+
+const MIME: &str = "video/webm";
+// how long the download link's URL is kept before it is let go
+const REVOKE_MS: i32 = 10_000;
+
+thread_local! {
+    static RECORDING: RefCell<Option<Recording>> = const { RefCell::new(None) };
+}
+
+// A recording in progress, and the video it has gathered so far.
+struct Recording {
+    recorder: MediaRecorder,
+    chunks: Rc<RefCell<Vec<Blob>>>,
+    // kept alive for as long as the recorder can call it
+    _on_data: Closure<dyn FnMut(BlobEvent)>,
+}
+
+/// Starts recording the game's canvas as WebM video, at whatever it shows
+/// from menus to the game-over screen. Does nothing if a recording is
+/// already running.
+#[wasm_bindgen]
+pub fn start_recording() -> Result<(), JsValue> {
+    if recording() {
+        return Ok(());
+    }
+    let canvas: HtmlCanvasElement = web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.get_element_by_id("game"))
+        .ok_or("no canvas")?
+        .dyn_into()?;
+    let options = MediaRecorderOptions::new();
+    options.set_mime_type(MIME);
+    let recorder = MediaRecorder::new_with_media_stream_and_media_recorder_options(
+        &canvas.capture_stream()?,
+        &options,
+    )?;
+    let chunks = Rc::new(RefCell::new(Vec::new()));
+    let on_data = {
+        let chunks = chunks.clone();
+        Closure::wrap(Box::new(move |event: BlobEvent| {
+            if let Some(blob) = event.data() {
+                chunks.borrow_mut().push(blob);
+            }
+        }) as Box<dyn FnMut(BlobEvent)>)
+    };
+    recorder.set_ondataavailable(Some(on_data.as_ref().unchecked_ref()));
+    recorder.start()?;
+    RECORDING.with(|r| {
+        r.replace(Some(Recording {
+            recorder,
+            chunks,
+            _on_data: on_data,
+        }))
+    });
+    Ok(())
+}
+
+/// Stops the recording and downloads it as `snake-run.webm`. Does nothing
+/// if there isn't one.
+#[wasm_bindgen]
+pub fn stop_recording() -> Result<(), JsValue> {
+    let Some(recording) = RECORDING.with(|r| r.take()) else {
+        return Ok(());
+    };
+    let recorder = recording.recorder.clone();
+    // the last of the video arrives just before the recorder stops
+    let on_stop = Closure::once_into_js(move || {
+        let _ = download(&recording.chunks.borrow());
+    });
+    recorder.set_onstop(Some(on_stop.unchecked_ref()));
+    recorder.stop()
+}
+
+/// Whether a recording is running.
+#[wasm_bindgen]
+pub fn recording() -> bool {
+    RECORDING.with(|r| r.borrow().is_some())
+}
+
+fn download(chunks: &[Blob]) -> Result<(), JsValue> {
+    let parts: js_sys::Array = chunks.iter().collect();
+    let bag = BlobPropertyBag::new();
+    bag.set_type(MIME);
+    let video = Blob::new_with_blob_sequence_and_options(&parts, &bag)?;
+    let url = Url::create_object_url_with_blob(&video)?;
+    crate::download(&url, "snake-run.webm")?;
+    let window = web_sys::window().ok_or("no window")?;
+    let revoke = Closure::once_into_js(move || {
+        let _ = Url::revoke_object_url(&url);
+    });
+    window
+        .set_timeout_with_callback_and_timeout_and_arguments_0(revoke.unchecked_ref(), REVOKE_MS)?;
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use web_sys::{CanvasRenderingContext2d, Document};
+
+use crate::webgl::Gl;
+
+/// What draws the board. The HUD, text and overlays always go through the
+/// 2D canvas; this only decides how the cells under them are filled.
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// The 2D canvas, with sprites, shapes and idle animations.
+    #[default]
+    Canvas,
+    /// Flat cells as instanced quads through WebGL, which keeps up on large
+    /// boards with long snakes where the 2D canvas runs out of fill rate.
+    Webgl,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 2] = [Backend::Canvas, Backend::Webgl];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Canvas => "canvas",
+            Backend::Webgl => "webgl",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|b| b.name() == name)
+    }
+}
+
+/// A filled rectangle in CSS pixels, in any CSS colour, at `alpha`.
+pub struct Quad<'a> {
+    pub rect: (f64, f64, f64, f64),
+    pub colour: &'a str,
+    pub alpha: f64,
+}
+
+/// A way of drawing the board's cells other than the 2D canvas's own.
+pub trait Renderer {
+    /// Sizes the drawing surface to a board `size` CSS pixels across, with
+    /// `scale` device pixels to each.
+    fn resize(&self, size: (f64, f64), scale: f64);
+
+    /// Draws `quads` in order onto `target`, over whatever it already
+    /// holds, through its current transform.
+    fn draw(&self, target: &CanvasRenderingContext2d, quads: &[Quad]) -> Result<(), JsValue>;
+}
+
+/// Starts the renderer for `backend`. `None` means the board is drawn on
+/// the 2D canvas, which is also what happens when WebGL isn't available.
+pub fn start(document: &Document, backend: Backend) -> Option<Box<dyn Renderer>> {
+    match backend {
+        Backend::Canvas => None,
+        Backend::Webgl => Gl::new(document)
+            .ok()
+            .map(|gl| Box::new(gl) as Box<dyn Renderer>),
+    }
+}
@@ -0,0 +1,110 @@
+use serde::Deserialize;
+
+use crate::core::board::{Board, Event, Shrink, Snake};
+use crate::core::bot::{self, Personality};
+use crate::core::names;
+use crate::core::rng::Rng;
+use crate::core::{HEIGHT, WIDTH};
+
+pub const MAX_OPPONENTS: usize = 3;
+
+// where each snake starts and which way it heads, the player's first
+const SPAWNS: [((i32, i32), (i32, i32)); MAX_OPPONENTS + 1] = [
+    ((WIDTH / 4, HEIGHT / 2), (1, 0)),
+    ((WIDTH * 3 / 4, HEIGHT / 2), (-1, 0)),
+    ((WIDTH / 2, HEIGHT / 4), (1, 0)),
+    ((WIDTH / 2, HEIGHT * 3 / 4), (-1, 0)),
+];
+
+/// How a battle royale is set up: the bots to outlast, and how fast the
+/// arena closes in on them.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct RoyaleConfig {
+    /// Bots playing against the player, from 1 to `MAX_OPPONENTS`.
+    pub opponents: usize,
+    /// Seconds between the arena's outer ring closing.
+    pub shrink_secs: f64,
+    /// The narrowest the arena closes to, in cells.
+    pub min_side: i32,
+}
+
+impl Default for RoyaleConfig {
+    fn default() -> Self {
+        Self {
+            opponents: MAX_OPPONENTS,
+            shrink_secs: 10.0,
+            min_side: 6,
+        }
+    }
+}
+
+impl RoyaleConfig {
+    pub fn valid(&self) -> bool {
+        (1..=MAX_OPPONENTS).contains(&self.opponents)
+            && self.shrink_secs > 0.0
+            && (2..=WIDTH.min(HEIGHT)).contains(&self.min_side)
+    }
+
+    /// The shrink schedule for a game clock ticking every `tick_ms`. The
+    /// board counts in ticks, so the schedule is fixed for the whole game
+    /// however much it speeds up.
+    pub fn shrink(&self, tick_ms: u32) -> Shrink {
+        let every = (self.shrink_secs * 1000.0 / f64::from(tick_ms)).round() as u32;
+        Shrink {
+            every: every.max(1),
+            min_side: self.min_side,
+        }
+    }
+
+    /// The player's snake, then one for each opponent.
+    pub fn snakes(&self) -> Vec<Snake> {
+        SPAWNS[..=self.opponents]
+            .iter()
+            .map(|&(head, dir)| Snake::new(head, dir))
+            .collect()
+    }
+}
+
+/// A battle royale in progress. The player is snake 0 and the bots follow.
+pub struct Royale {
+    pub config: RoyaleConfig,
+    /// Each bot's name and how it plays.
+    pub bots: Vec<(String, Personality)>,
+}
+
+impl Royale {
+    pub fn new(config: RoyaleConfig, rng: &mut Rng) -> Self {
+        let bots = (0..config.opponents)
+            .map(|_| {
+                let name = names::generate(rng);
+                let i = rng.below(Personality::ALL.len() as i32) as usize;
+                (name, Personality::ALL[i])
+            })
+            .collect();
+        Self { config, bots }
+    }
+
+    /// The bots' moves for the coming tick.
+    pub fn turns(&self, board: &Board) -> Vec<Event> {
+        self.bots
+            .iter()
+            .enumerate()
+            .map(|(i, (_, personality))| (i + 1, personality))
+            .filter(|&(snake, _)| board.snakes[snake].alive)
+            .filter_map(|(snake, &personality)| {
+                let food = board.nearest_food(snake)?;
+                Some(Event::TurnRequested {
+                    snake,
+                    dir: bot::steer(board, snake, food, personality),
+                })
+            })
+            .collect()
+    }
+
+    /// How many snakes are still in, out of how many started.
+    pub fn standing(board: &Board) -> (usize, usize) {
+        let alive = board.snakes.iter().filter(|s| s.alive).count();
+        (alive, board.snakes.len())
+    }
+}
@@ -0,0 +1,150 @@
+use crate::core::board::Board;
+use crate::core::bot;
+use crate::format;
+
+// foods eaten this many ticks or less apart make a combo
+const COMBO_TICKS: u32 = 30;
+// a narrow escape ranks with a combo of this many foods
+const ESCAPE_WEIGHT: u32 = 3;
+
+/// Something worth showing off from a run.
+#[derive(Clone, Copy)]
+pub enum Highlight {
+    /// Foods eaten in quick succession.
+    Combo(u32),
+    /// Survived a tick with only one way out.
+    Escape,
+}
+
+impl Highlight {
+    fn weight(self) -> u32 {
+        match self {
+            Highlight::Combo(foods) => foods,
+            Highlight::Escape => ESCAPE_WEIGHT,
+        }
+    }
+
+    pub fn caption(self) -> String {
+        match self {
+            Highlight::Combo(foods) => format!("{foods}-food combo"),
+            Highlight::Escape => "Narrow escape".to_string(),
+        }
+    }
+}
+
+/// The tick a highlight happened at.
+#[derive(Clone, Copy)]
+pub struct Moment {
+    pub tick: u32,
+    pub highlight: Highlight,
+}
+
+/// Figures gathered while a run is played, shown live in the stats panel
+/// and again on the game-over screen.
+#[derive(Clone, Copy, Default)]
+pub struct RunStats {
+    pub length: usize,
+    pub max_length: usize,
+    pub foods: u32,
+    /// Time spent playing, leaving out pauses.
+    pub played_ms: f64,
+    /// Ticks per second at the latest step of the game clock.
+    pub tick_rate: f64,
+    /// The most ticks per second the run reached.
+    pub top_speed: f64,
+    /// The run's best highlight so far; the earliest wins a tie.
+    pub best_moment: Option<Moment>,
+    // foods in the current combo, and the tick of the latest
+    combo: u32,
+    last_food: u32,
+}
+
+impl RunStats {
+    pub fn new(board: &Board, snake: usize) -> Self {
+        let length = board.snakes[snake].body.len();
+        Self {
+            length,
+            max_length: length,
+            ..Self::default()
+        }
+    }
+
+    /// Counts the tick that took `before` to `board`.
+    pub fn ticked(&mut self, before: &Board, board: &Board, snake: usize) {
+        if board.ate(before, snake) {
+            self.foods += 1;
+            if self.combo > 0 && board.tick.saturating_sub(self.last_food) <= COMBO_TICKS {
+                self.combo += 1;
+                self.highlight(board.tick, Highlight::Combo(self.combo));
+            } else {
+                self.combo = 1;
+            }
+            self.last_food = board.tick;
+        }
+        if board.snakes[snake].alive && bot::exits(before, snake) == 1 {
+            self.highlight(board.tick, Highlight::Escape);
+        }
+        self.length = board.snakes[snake].body.len();
+        self.max_length = self.max_length.max(self.length);
+    }
+
+    fn highlight(&mut self, tick: u32, highlight: Highlight) {
+        if self
+            .best_moment
+            .is_none_or(|m| highlight.weight() > m.highlight.weight())
+        {
+            self.best_moment = Some(Moment { tick, highlight });
+        }
+    }
+
+    /// Counts one step of the game clock at `speed` ticks per step.
+    pub fn played(&mut self, tick_ms: f64, speed: f64) {
+        self.played_ms += tick_ms;
+        self.tick_rate = speed * 1000.0 / tick_ms;
+        self.top_speed = self.top_speed.max(self.tick_rate);
+    }
+
+    /// Foods in the combo still running at `tick`, if any.
+    pub fn combo(&self, tick: u32) -> u32 {
+        if tick.saturating_sub(self.last_food) <= COMBO_TICKS {
+            self.combo
+        } else {
+            0
+        }
+    }
+
+    pub fn foods_per_minute(&self) -> f64 {
+        if self.played_ms > 0.0 {
+            f64::from(self.foods) * 60_000.0 / self.played_ms
+        } else {
+            0.0
+        }
+    }
+
+    /// Time played as `m:ss`.
+    pub fn clock(&self) -> String {
+        let secs = (self.played_ms / 1000.0) as u32;
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+
+    /// The figures for the stats panel during play.
+    pub fn live(&self) -> [String; 4] {
+        [
+            format!("Length {}", self.length),
+            format!("Time {}", self.clock()),
+            format!("Food/min {:.1}", self.foods_per_minute()),
+            format!("Tick rate {:.1}/s", self.tick_rate),
+        ]
+    }
+
+    /// The figures for the game-over screen.
+    pub fn lines(&self, score: i32) -> [String; 5] {
+        [
+            format!("Score {}", format::score(score)),
+            format!("Longest {}", self.max_length),
+            format!("Food eaten {}", self.foods),
+            format!("Survived {}", self.clock()),
+            format!("Top speed {:.1} ticks/s", self.top_speed),
+        ]
+    }
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::board::{Board, Event};
+use crate::core::rng::Rng;
+use crate::storage;
+
+const KEY: &str = "snake_game.save";
+
+/// A single-player run in progress, kept across page reloads.
+#[derive(Serialize, Deserialize)]
+pub struct SavedRun {
+    pub board: Board,
+    pub rng: Rng,
+    pub log: Vec<Event>,
+    #[serde(default)]
+    pub rewound: bool,
+}
+
+pub fn store(run: &SavedRun) {
+    storage::store(KEY, run);
+}
+
+/// Loads the saved run, if any, and removes it so it is only resumed once.
+/// A run saved with rules out of range is dropped.
+pub fn take() -> Option<SavedRun> {
+    let run = storage::load::<SavedRun>(KEY).filter(|run| run.board.rules.valid());
+    storage::remove(KEY);
+    run
+}
@@ -0,0 +1,48 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Version of everything written to storage or sent to a peer as JSON. Bump
+/// it whenever one of those shapes changes in a way the serde defaults
+/// can't absorb, and add a step to `MIGRATIONS`.
+pub const VERSION: u32 = 1;
+
+// `MIGRATIONS[n]` upgrades data written at version n to version n + 1.
+const MIGRATIONS: [fn(Value) -> Value; VERSION as usize] = [unwrapped];
+
+// Version 0 is anything stored before the envelope existed. Its shapes are
+// the same as version 1's.
+fn unwrapped(data: Value) -> Value {
+    data
+}
+
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    version: u32,
+    data: &'a T,
+}
+
+/// `value` as `{"version": VERSION, "data": ...}`.
+pub fn to_json<T: Serialize>(value: &T) -> Option<String> {
+    serde_json::to_string(&Envelope {
+        version: VERSION,
+        data: value,
+    })
+    .ok()
+}
+
+/// Reads JSON written by `to_json` at this or any earlier version. Data
+/// from a newer version is rejected rather than misread.
+pub fn from_json<T: DeserializeOwned>(json: &str) -> Option<T> {
+    let (version, mut data) = match serde_json::from_str(json).ok()? {
+        Value::Object(mut map) if map.contains_key("version") && map.contains_key("data") => {
+            (map["version"].as_u64()?, map.remove("data")?)
+        }
+        legacy => (0, legacy),
+    };
+    let version = usize::try_from(version).ok()?;
+    for migrate in MIGRATIONS.get(version..)? {
+        data = migrate(data);
+    }
+    serde_json::from_value(data).ok()
+}
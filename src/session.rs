@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::{callbacks, format, highscores, storage};
+
+const KEY: &str = "snake_game.today";
+
+/// One finished game, as listed in the session summary.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Run {
+    pub score: i32,
+    pub length: usize,
+    pub mode: String,
+    pub secs: u32,
+}
+
+/// The games finished on this device today, whichever tab played them.
+#[derive(Default, Serialize, Deserialize)]
+struct Day {
+    date: String,
+    runs: Vec<Run>,
+}
+
+fn today() -> Day {
+    let date = highscores::today();
+    storage::load::<Day>(KEY)
+        .filter(|day| day.date == date)
+        .unwrap_or(Day {
+            date,
+            runs: Vec::new(),
+        })
+}
+
+pub fn record(run: Run) {
+    let mut day = today();
+    day.runs.push(run);
+    storage::store(KEY, &day);
+}
+
+/// Today's games as Markdown that still reads well as plain text.
+pub fn summary() -> String {
+    let day = today();
+    let mut text = format!("**Snake \u{2014} {}**\n\n", day.date);
+    let Some(best) = day.runs.iter().map(|r| r.score).max() else {
+        text.push_str("No games finished yet today.\n");
+        return text;
+    };
+    let played: u32 = day.runs.iter().map(|r| r.secs).sum();
+    text.push_str(&format!(
+        "{} games in {}:{:02}, best score {}\n\n",
+        day.runs.len(),
+        played / 60,
+        played % 60,
+        format::score(best)
+    ));
+    for (i, run) in day.runs.iter().enumerate() {
+        text.push_str(&format!(
+            "{}. {} \u{2014} {} points, length {}, {}:{:02}\n",
+            i + 1,
+            run.mode,
+            format::score(run.score),
+            run.length,
+            run.secs / 60,
+            run.secs % 60
+        ));
+    }
+    text
+}
+
+/// Puts a summary of today's games on the clipboard and returns it. A page
+/// that set `on_summary` gets the summary instead, to share its own way.
+#[wasm_bindgen]
+pub fn copy_session_summary() -> String {
+    let text = summary();
+    let summary = callbacks::Summary { text: text.clone() };
+    if !callbacks::summary(&summary) {
+        if let Some(window) = web_sys::window() {
+            let _ = window.navigator().clipboard().write_text(&text);
+        }
+    }
+    text
+}
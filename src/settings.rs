@@ -0,0 +1,352 @@
+use std::cell::RefCell;
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::core::board::{Mutators, Rules, WrapConfig, MAX_FOOD, MAX_OBSTACLES};
+use crate::core::bot::Personality;
+use crate::hud::HudLayout;
+use crate::palette::Scheme;
+use crate::privacy::Privacy;
+use crate::renderer::Backend;
+use crate::storage;
+use crate::theme::Theme;
+
+const KEY: &str = "snake_game.settings";
+pub const MIN_TICK_MS: u32 = 40;
+pub const MAX_TICK_MS: u32 = 400;
+
+/// Every player-facing option, kept in localStorage. Subsystems read their
+/// options from here rather than holding their own copies.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Milliseconds per game tick.
+    pub tick_ms: u32,
+    /// Which edges snakes wrap around instead of crashing into.
+    pub wrap: WrapConfig,
+    /// Whether single-player games speed up as the score grows, as the
+    /// `speed_ramp` plugin does.
+    pub speed_ramp: bool,
+    /// How many pieces of food are out at once.
+    pub food_count: u32,
+    /// How much of the arena is blocked by obstacles, in percent.
+    pub obstacles: u32,
+    /// Name of the theme the board is drawn in.
+    pub theme: String,
+    /// Themes made in the theme editor or imported.
+    pub custom_themes: Vec<Theme>,
+    /// URL of a sprite sheet to draw the snake and food with.
+    pub sprite_sheet: Option<String>,
+    /// Colours for the snakes, food and hazards.
+    pub palette: Scheme,
+    pub bindings: Bindings,
+    /// Master volume from 0 to 1, applied on top of the channels below.
+    pub volume: f64,
+    pub music_volume: f64,
+    /// Volume of the sound effects.
+    pub sfx_volume: f64,
+    /// Whether background music plays during games.
+    pub music: bool,
+    /// Forced battery saver, or `None` to follow the battery level.
+    pub battery_saver: Option<bool>,
+    /// Seconds without input before a game pauses; 0 is off.
+    pub idle_timeout: u32,
+    pub initials: Option<String>,
+    /// The name this player goes by without initials, made up once.
+    pub guest_name: Option<String>,
+    /// Whether the debug overlay is drawn over the board.
+    pub debug_hud: bool,
+    /// Whether the live stats panel is drawn over the board.
+    pub stats_panel: bool,
+    /// Which HUD widgets are drawn, and where.
+    pub hud: HudLayout,
+    /// Twists for the next run.
+    pub mutators: Mutators,
+    /// Whether the theme's scenery drifts behind the board.
+    pub backdrop: bool,
+    /// Whether snakes glide between cells instead of stepping.
+    pub smooth: bool,
+    /// How the autopilot plays.
+    pub autopilot: Personality,
+    /// Whether the board has grid lines and guides through the head's row
+    /// and column, toggled with G.
+    pub guides: bool,
+    /// Whether an arrow points the way to the food, as the planner bot
+    /// would go.
+    pub hints: bool,
+    /// Whether all text is set in the pixel font, whatever the theme.
+    pub pixel_font: bool,
+    /// What the game may keep or send, and for how long.
+    pub privacy: Privacy,
+    /// What draws the board. It can be switched mid-game, with M or
+    /// `set_renderer`, and the choice is kept for later visits.
+    pub renderer: Backend,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            tick_ms: 100,
+            wrap: WrapConfig::both(true),
+            speed_ramp: false,
+            food_count: 1,
+            obstacles: 0,
+            theme: "dark".to_string(),
+            custom_themes: Vec::new(),
+            sprite_sheet: None,
+            palette: Scheme::Standard,
+            bindings: Bindings::default(),
+            volume: 1.0,
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            music: true,
+            battery_saver: None,
+            idle_timeout: 0,
+            initials: None,
+            guest_name: None,
+            debug_hud: false,
+            stats_panel: false,
+            hud: HudLayout::default(),
+            mutators: Mutators::default(),
+            backdrop: true,
+            smooth: true,
+            autopilot: Personality::Greedy,
+            guides: false,
+            hints: false,
+            pixel_font: false,
+            privacy: Privacy::default(),
+            renderer: Backend::Canvas,
+        }
+    }
+}
+
+/// The keys that steer the snake, as `KeyboardEvent.key` values.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bindings {
+    pub up: String,
+    pub down: String,
+    pub left: String,
+    pub right: String,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            up: "ArrowUp".to_string(),
+            down: "ArrowDown".to_string(),
+            left: "ArrowLeft".to_string(),
+            right: "ArrowRight".to_string(),
+        }
+    }
+}
+
+impl Bindings {
+    pub fn dir(&self, key: &str) -> Option<(i32, i32)> {
+        if key == self.up {
+            Some((0, -1))
+        } else if key == self.down {
+            Some((0, 1))
+        } else if key == self.left {
+            Some((-1, 0))
+        } else if key == self.right {
+            Some((1, 0))
+        } else {
+            None
+        }
+    }
+}
+
+thread_local! {
+    static SETTINGS: RefCell<Settings> = RefCell::new(Settings::default());
+}
+
+pub fn load() {
+    SETTINGS.set(storage::load(KEY).unwrap_or_default());
+}
+
+pub fn with<R>(f: impl FnOnce(&Settings) -> R) -> R {
+    SETTINGS.with(|s| f(&s.borrow()))
+}
+
+/// The board rules picked here, for the next game. A food count or
+/// obstacles out of range, from settings edited in storage, fall back to
+/// the defaults.
+pub fn rules() -> Rules {
+    let rules = with(|s| Rules {
+        wrap: s.wrap,
+        mutators: s.mutators,
+        food_count: s.food_count,
+        obstacles: s.obstacles,
+        ..Rules::default()
+    });
+    if rules.valid() {
+        rules
+    } else {
+        Rules {
+            wrap: rules.wrap,
+            mutators: rules.mutators,
+            ..Rules::default()
+        }
+    }
+}
+
+/// Changes the settings and saves them.
+pub fn update(f: impl FnOnce(&mut Settings)) {
+    SETTINGS.with(|s| {
+        f(&mut s.borrow_mut());
+        storage::store(KEY, &*s.borrow());
+    });
+}
+
+/// The current settings as JSON, for a settings panel.
+#[wasm_bindgen]
+pub fn get_settings() -> String {
+    with(|s| serde_json::to_string(s).unwrap_or_default())
+}
+
+/// Restores every setting to its default.
+#[wasm_bindgen]
+pub fn reset_settings() {
+    update(|s| *s = Settings::default());
+}
+
+/// `set_tick_ms` under its older name.
+#[wasm_bindgen]
+pub fn set_speed(tick_ms: u32) {
+    let _ = crate::set_tick_ms(tick_ms);
+}
+
+/// Whether snakes wrap around every edge; applies from the next game.
+#[wasm_bindgen]
+pub fn set_wrap(on: bool) {
+    update(|s| s.wrap = WrapConfig::both(on));
+}
+
+/// Whether snakes wrap across the left and right edges (`x`) and the top
+/// and bottom ones (`y`), with walls on the others; applies from the next
+/// game.
+#[wasm_bindgen]
+pub fn set_wrap_axes(x: bool, y: bool) {
+    update(|s| s.wrap = WrapConfig { x, y });
+}
+
+/// Whether single-player games speed up as the score grows, to at most
+/// twice the set speed; applies from the next game.
+#[wasm_bindgen]
+pub fn set_speed_ramp(on: bool) {
+    update(|s| s.speed_ramp = on);
+}
+
+/// How many pieces of food are out at once, from 1 to 5, from the next
+/// game.
+#[wasm_bindgen]
+pub fn set_food_count(count: u32) -> Result<(), JsValue> {
+    if !(1..=MAX_FOOD).contains(&count) {
+        return Err(JsValue::from_str("the food count must be 1 to 5"));
+    }
+    update(|s| s.food_count = count);
+    Ok(())
+}
+
+/// Blocks `percent` of the arena, up to 20, with obstacles from the next
+/// game; 0 clears them.
+#[wasm_bindgen]
+pub fn set_obstacles(percent: u32) -> Result<(), JsValue> {
+    if percent > MAX_OBSTACLES {
+        return Err(JsValue::from_str("obstacles can cover at most 20%"));
+    }
+    update(|s| s.obstacles = percent);
+    Ok(())
+}
+
+/// Turns a run mutator on or off from the next game: `"poison"`,
+/// `"rotate"`, `"double_speed"`, `"tiny"` or `"portals"`.
+#[wasm_bindgen]
+pub fn set_mutator(name: &str, on: bool) -> Result<(), JsValue> {
+    let mut result = Ok(());
+    update(|s| match s.mutators.get_mut(name) {
+        Some(flag) => *flag = on,
+        None => result = Err(JsValue::from_str("unknown mutator")),
+    });
+    result
+}
+
+/// Binds `action` (`"up"`, `"down"`, `"left"` or `"right"`) to a key.
+#[wasm_bindgen]
+pub fn set_binding(action: &str, key: String) -> Result<(), JsValue> {
+    let mut result = Ok(());
+    update(|s| match action {
+        "up" => s.bindings.up = key,
+        "down" => s.bindings.down = key,
+        "left" => s.bindings.left = key,
+        "right" => s.bindings.right = key,
+        _ => result = Err(JsValue::from_str("unknown action")),
+    });
+    result
+}
+
+/// Sets a volume from 0 to 1: `"master"`, `"music"` or `"sfx"`.
+#[wasm_bindgen]
+pub fn set_volume(channel: &str, level: f64) -> Result<(), JsValue> {
+    let level = level.clamp(0.0, 1.0);
+    let mut result = Ok(());
+    update(|s| match channel {
+        "master" => s.volume = level,
+        "music" => s.music_volume = level,
+        "sfx" => s.sfx_volume = level,
+        _ => result = Err(JsValue::from_str("unknown channel")),
+    });
+    result
+}
+
+/// Turns the theme's animated background on or off. It also stays still
+/// in battery saver and when the system asks for reduced motion.
+#[wasm_bindgen]
+pub fn set_backdrop(on: bool) {
+    update(|s| s.backdrop = on);
+}
+
+/// Whether snakes glide smoothly from cell to cell, a tick behind the game,
+/// or jump a whole cell each tick.
+#[wasm_bindgen]
+pub fn set_smooth(on: bool) {
+    update(|s| s.smooth = on);
+}
+
+/// How the autopilot plays: `"greedy"`, `"cautious"`, `"blocker"` or
+/// `"wanderer"`.
+#[wasm_bindgen]
+pub fn set_autopilot(personality: &str) -> Result<(), JsValue> {
+    let personality =
+        Personality::parse(personality).ok_or_else(|| JsValue::from_str("unknown personality"))?;
+    update(|s| s.autopilot = personality);
+    Ok(())
+}
+
+/// Shows or hides the debug overlay, which F3 also toggles.
+#[wasm_bindgen]
+pub fn set_debug_hud(on: bool) {
+    update(|s| s.debug_hud = on);
+}
+
+/// Shows or hides the grid and the guides through the head, which G also
+/// toggles.
+#[wasm_bindgen]
+pub fn set_guides(on: bool) {
+    update(|s| s.guides = on);
+}
+
+/// Shows or hides the hint arrow ahead of the snake's head, pointing the
+/// shortest safe way to the food.
+#[wasm_bindgen]
+pub fn set_hints(on: bool) {
+    update(|s| s.hints = on);
+}
+
+/// Shows or hides the live stats panel, which Tab also toggles.
+#[wasm_bindgen]
+pub fn set_stats_panel(on: bool) {
+    update(|s| s.stats_panel = on);
+}
@@ -0,0 +1,269 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use wasm_bindgen::prelude::*;
+use web_sys::{CanvasRenderingContext2d, HtmlImageElement};
+
+use crate::{assets, settings};
+
+thread_local! {
+    static SHEET: RefCell<Option<HtmlImageElement>> = const { RefCell::new(None) };
+}
+
+/// A tile of the sprite sheet. The sheet is one row of square tiles in
+/// this order, each drawn facing right: the head, a straight piece of
+/// body, a corner joining the left and bottom edges, the tail with the
+/// body off to its right, and the food.
+#[derive(Clone, Copy)]
+pub enum Tile {
+    Head,
+    Body,
+    Corner,
+    Tail,
+    Food,
+}
+
+/// Starts loading the sprite sheet named in the settings, if any.
+pub fn load() {
+    let image = settings::with(|s| s.sprite_sheet.clone())
+        .and_then(|url| assets::image("sprite sheet", &url, js_sys::Date::now()));
+    SHEET.set(image);
+}
+
+/// The sprite sheet, once it has loaded. Until then the board is drawn in
+/// flat squares.
+pub fn sheet() -> Option<HtmlImageElement> {
+    SHEET.with(|sheet| {
+        sheet
+            .borrow()
+            .clone()
+            .filter(|image| image.complete() && image.natural_width() > 0)
+    })
+}
+
+/// Draws `tile` over the cell at `(x, y)`, turned clockwise by `turns`
+/// quarter turns.
+pub fn draw(
+    ctx: &CanvasRenderingContext2d,
+    sheet: &HtmlImageElement,
+    tile: Tile,
+    turns: u32,
+    (x, y): (f64, f64),
+    cell: f64,
+) -> Result<(), JsValue> {
+    let size = f64::from(sheet.natural_height());
+    ctx.save();
+    ctx.translate(x + cell / 2.0, y + cell / 2.0)?;
+    ctx.rotate(f64::from(turns) * FRAC_PI_2)?;
+    let drawn = ctx.draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+        sheet,
+        size * tile as u32 as f64,
+        0.0,
+        size,
+        size,
+        -cell / 2.0,
+        -cell / 2.0,
+        cell,
+        cell,
+    );
+    ctx.restore();
+    drawn
+}
+
+/// Draws `tile` over the cell at `(x, y)` without a sprite sheet, in
+/// `colour`: the head rounded at the front with eyes looking the way it
+/// goes, the tail rounded at the end and corners curved round the bend.
+pub fn draw_flat(
+    ctx: &CanvasRenderingContext2d,
+    tile: Tile,
+    turns: u32,
+    (x, y): (f64, f64),
+    cell: f64,
+    colour: &str,
+) -> Result<(), JsValue> {
+    let half = cell / 2.0;
+    ctx.save();
+    ctx.translate(x + half, y + half)?;
+    ctx.rotate(f64::from(turns) * FRAC_PI_2)?;
+    ctx.set_fill_style_str(colour);
+    ctx.begin_path();
+    // each tile faces right, as on the sprite sheet
+    match tile {
+        Tile::Head => {
+            ctx.rect(-half, -half, half, cell);
+            ctx.arc(0.0, 0.0, half, -FRAC_PI_2, FRAC_PI_2)?;
+        }
+        Tile::Tail => {
+            ctx.rect(0.0, -half, half, cell);
+            ctx.arc(0.0, 0.0, half, FRAC_PI_2, PI + FRAC_PI_2)?;
+        }
+        // a quarter circle round the corner the left and bottom edges meet
+        Tile::Corner => {
+            ctx.move_to(-half, half);
+            ctx.arc(-half, half, cell, -FRAC_PI_2, 0.0)?;
+        }
+        Tile::Body | Tile::Food => ctx.rect(-half, -half, cell, cell),
+    }
+    ctx.fill();
+    if let Tile::Head = tile {
+        for side in [-1.0, 1.0] {
+            let eye = (cell * 0.1, side * cell * 0.22);
+            ctx.set_fill_style_str("white");
+            ctx.begin_path();
+            ctx.arc(eye.0, eye.1, cell * 0.12, 0.0, 2.0 * PI)?;
+            ctx.fill();
+            ctx.set_fill_style_str("black");
+            ctx.begin_path();
+            ctx.arc(eye.0 + cell * 0.05, eye.1, cell * 0.06, 0.0, 2.0 * PI)?;
+            ctx.fill();
+        }
+    }
+    ctx.restore();
+    Ok(())
+}
+
+/// Draws a portal over the cell at `(x, y)` in `colour`: squares inside
+/// one another, turned alternately either way by `spin` of a full turn.
+pub fn portal(
+    ctx: &CanvasRenderingContext2d,
+    (x, y): (f64, f64),
+    cell: f64,
+    colour: &str,
+    spin: f64,
+) -> Result<(), JsValue> {
+    let half = cell / 2.0;
+    ctx.save();
+    ctx.translate(x + half, y + half)?;
+    ctx.set_stroke_style_str(colour);
+    ctx.set_line_width((cell / 10.0).max(1.0));
+    for ring in 0..3 {
+        let side = cell * (0.9 - 0.25 * f64::from(ring));
+        let way = if ring % 2 == 0 { 1.0 } else { -1.0 };
+        ctx.save();
+        ctx.rotate(way * spin * 2.0 * PI)?;
+        ctx.stroke_rect(-side / 2.0, -side / 2.0, side, side);
+        ctx.restore();
+    }
+    ctx.restore();
+    Ok(())
+}
+
+/// Draws an arrow filling the cell at `(x, y)`, pointing along `dir`.
+pub fn arrow(
+    ctx: &CanvasRenderingContext2d,
+    (x, y): (f64, f64),
+    cell: f64,
+    dir: (i32, i32),
+    colour: &str,
+) -> Result<(), JsValue> {
+    let half = cell / 2.0;
+    ctx.save();
+    ctx.translate(x + half, y + half)?;
+    ctx.rotate(f64::from(turns_to(dir)) * FRAC_PI_2)?;
+    ctx.set_fill_style_str(colour);
+    ctx.begin_path();
+    // drawn pointing right, like the tiles
+    ctx.move_to(half * 0.8, 0.0);
+    ctx.line_to(-half * 0.6, -half * 0.7);
+    ctx.line_to(-half * 0.2, 0.0);
+    ctx.line_to(-half * 0.6, half * 0.7);
+    ctx.close_path();
+    ctx.fill();
+    ctx.restore();
+    Ok(())
+}
+
+/// Draws a forked tongue out of the front of the head in the cell at
+/// `(x, y)`, turned like the head, `out` of the way from 0 (in) to 1.
+pub fn tongue(
+    ctx: &CanvasRenderingContext2d,
+    turns: u32,
+    (x, y): (f64, f64),
+    cell: f64,
+    out: f64,
+) -> Result<(), JsValue> {
+    if out <= 0.0 {
+        return Ok(());
+    }
+    let half = cell / 2.0;
+    let tip = half + cell * 0.45 * out;
+    ctx.save();
+    ctx.translate(x + half, y + half)?;
+    ctx.rotate(f64::from(turns) * FRAC_PI_2)?;
+    ctx.set_stroke_style_str("crimson");
+    ctx.set_line_width((cell / 10.0).max(1.0));
+    ctx.begin_path();
+    ctx.move_to(half, 0.0);
+    ctx.line_to(tip, 0.0);
+    ctx.move_to(tip, 0.0);
+    ctx.line_to(tip + cell * 0.1 * out, -cell * 0.08);
+    ctx.move_to(tip, 0.0);
+    ctx.line_to(tip + cell * 0.1 * out, cell * 0.08);
+    ctx.stroke();
+    ctx.restore();
+    Ok(())
+}
+
+/// The tile for segment `i` of `body`, and how far to turn it. `dir` is
+/// where the snake is heading, for a snake that is only a head.
+pub fn segment(body: &VecDeque<(i32, i32)>, i: usize, dir: (i32, i32)) -> (Tile, u32) {
+    // the way from segment i to its neighbour j, across a wrapped edge too
+    let toward = |j: usize| {
+        let (a, b) = (body[i], body[j]);
+        (step(b.0 - a.0), step(b.1 - a.1))
+    };
+    let last = body.len() - 1;
+    if i == 0 {
+        let facing = if last == 0 {
+            dir
+        } else {
+            let (x, y) = toward(1);
+            (-x, -y)
+        };
+        (Tile::Head, turns_to(facing))
+    } else if i == last {
+        (Tile::Tail, turns_to(toward(i - 1)))
+    } else {
+        let (front, back) = (toward(i - 1), toward(i + 1));
+        if front == (-back.0, -back.1) {
+            (Tile::Body, turns_to(front) % 2)
+        } else {
+            // the corner tile joins the left and bottom edges
+            let turns = (0..4)
+                .find(|&t| {
+                    let ends = [turn((-1, 0), t), turn((0, 1), t)];
+                    ends.contains(&front) && ends.contains(&back)
+                })
+                .unwrap_or(0);
+            (Tile::Corner, turns)
+        }
+    }
+}
+
+// A neighbour's offset, as -1, 0 or 1 even where the board wraps.
+fn step(d: i32) -> i32 {
+    match d {
+        d if d > 1 => -1,
+        d if d < -1 => 1,
+        d => d,
+    }
+}
+
+// Quarter turns clockwise that take "right" to `dir`.
+fn turns_to(dir: (i32, i32)) -> u32 {
+    (0..4).find(|&t| turn((1, 0), t) == dir).unwrap_or(0)
+}
+
+fn turn((x, y): (i32, i32), turns: u32) -> (i32, i32) {
+    (0..turns).fold((x, y), |(x, y), _| (-y, x))
+}
+
+/// Draws the board from the sprite sheet at `url`, a PNG of five square
+/// tiles in a row (see the readme), or goes back to flat squares for
+/// `None`. Saved with the settings.
+#[wasm_bindgen]
+pub fn set_sprite_sheet(url: Option<String>) {
+    settings::update(|s| s.sprite_sheet = url);
+    load();
+}
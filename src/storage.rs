@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use web_sys::Storage;
+
+use crate::privacy::{self, Data};
+use crate::{schema, settings};
+
+// every key the game writes starts with this
+const PREFIX: &str = "snake_game.";
+// when each key under the player's privacy settings was last written
+const STAMPS: &str = "snake_game.stamps";
+
+// The keys that hold data the player can turn off or have expire. The
+// rest (settings, matches in progress) are needed to play.
+const GOVERNED: [(&str, Data); 3] = [
+    ("snake_game.save", Data::Replays),
+    ("snake_game.high_scores", Data::Stats),
+    ("snake_game.today", Data::Stats),
+];
+
+/// Reads `key`, unless the player's privacy settings no longer allow it to
+/// be kept, in which case it is deleted instead.
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    if !kept(key) {
+        remove(key);
+        return None;
+    }
+    let json = local()?.get_item(key).ok()??;
+    schema::from_json(&json)
+}
+
+/// Writes `value` under `key` if the player's privacy settings allow it.
+pub fn store<T: Serialize>(key: &str, value: &T) {
+    if governed(key).is_some_and(|data| !privacy::allows(data)) {
+        remove(key);
+        return;
+    }
+    let Some(json) = schema::to_json(value) else {
+        return;
+    };
+    if let Some(storage) = local() {
+        let _ = storage.set_item(key, &json);
+    }
+    if governed(key).is_some() {
+        stamp(key, Some(js_sys::Date::now()));
+    }
+}
+
+pub fn remove(key: &str) {
+    if let Some(storage) = local() {
+        let _ = storage.remove_item(key);
+    }
+    if governed(key).is_some() {
+        stamp(key, None);
+    }
+}
+
+/// Deletes whatever the privacy settings no longer allow to be kept.
+pub fn purge() {
+    for (key, _) in GOVERNED {
+        if !kept(key) {
+            remove(key);
+        }
+    }
+}
+
+/// Deletes every key the game has written.
+pub fn wipe() {
+    let Some(storage) = local() else {
+        return;
+    };
+    let len = storage.length().unwrap_or(0);
+    let keys: Vec<String> = (0..len)
+        .filter_map(|i| storage.key(i).ok().flatten())
+        .filter(|key| key.starts_with(PREFIX))
+        .collect();
+    for key in keys {
+        let _ = storage.remove_item(&key);
+    }
+}
+
+fn governed(key: &str) -> Option<Data> {
+    GOVERNED
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|&(_, data)| data)
+}
+
+// Whether `key` may still be kept. Anything written before it was stamped
+// counts as written now.
+fn kept(key: &str) -> bool {
+    let Some(data) = governed(key) else {
+        return true;
+    };
+    let now = js_sys::Date::now();
+    let saved = stamps().get(key).copied().unwrap_or(now);
+    settings::with(|s| s.privacy.allows(data) && s.privacy.fresh(saved, now))
+}
+
+fn stamps() -> HashMap<String, f64> {
+    local()
+        .and_then(|storage| storage.get_item(STAMPS).ok()?)
+        .and_then(|json| schema::from_json(&json))
+        .unwrap_or_default()
+}
+
+fn stamp(key: &str, at: Option<f64>) {
+    let mut stamps = stamps();
+    match at {
+        Some(at) => stamps.insert(key.to_string(), at),
+        None => stamps.remove(key),
+    };
+    if let (Some(storage), Some(json)) = (local(), schema::to_json(&stamps)) {
+        let _ = storage.set_item(STAMPS, &json);
+    }
+}
+
+fn local() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
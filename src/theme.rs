@@ -0,0 +1,198 @@
+use std::cell::Cell;
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::backdrop::Scenery;
+use crate::{assets, settings};
+
+/// The pixel font's family, and where the page serves it from.
+pub const PIXEL_FONT: &str = "Pixel";
+pub const PIXEL_FONT_URL: &str = "fonts/pixel.woff2";
+/// The theme name that follows the system: dark or light as the system is.
+pub const AUTO: &str = "auto";
+
+thread_local! {
+    // whether the system prefers dark colours, kept up to date by
+    // `watch_scheme`
+    static PREFERS_DARK: Cell<bool> = const { Cell::new(true) };
+}
+
+/// How the board looks. The snakes' and food's colours come from the
+/// palette, so a theme can't undo a colour-blind setting; it decides
+/// everything around them.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Theme {
+    pub name: String,
+    pub background: String,
+    /// Colour for text drawn on the board.
+    pub ink: String,
+    /// Colour of lines between the cells, if there are any.
+    pub grid: Option<String>,
+    /// How much a snake fades from head to tail, from 0 (not at all) to 1
+    /// (into the background).
+    pub fade: f64,
+    /// Drawn in place of the food's square, unless empty.
+    pub food: String,
+    /// CSS font family for text on the board.
+    pub font: String,
+    pub scenery: Scenery,
+}
+
+impl Theme {
+    /// The theme's font at `px` pixels, for `set_font`.
+    pub fn font(&self, px: f64) -> String {
+        format!("{px}px {}", self.font)
+    }
+
+    /// A fixed-width font at `px` pixels for the overlays that line up in
+    /// columns: the theme's own if it ends in `monospace`, as the pixel
+    /// font does, or else plain monospace.
+    pub fn mono(&self, px: f64) -> String {
+        if self.font.ends_with("monospace") {
+            self.font(px)
+        } else {
+            format!("{px}px monospace")
+        }
+    }
+}
+
+/// The themes that come with the game, `dark` first.
+pub fn builtin() -> Vec<Theme> {
+    let theme = |name: &str, background: &str, ink: &str, grid: Option<&str>| Theme {
+        name: name.to_string(),
+        background: background.to_string(),
+        ink: ink.to_string(),
+        grid: grid.map(str::to_string),
+        fade: 0.0,
+        food: String::new(),
+        font: "sans-serif".to_string(),
+        scenery: Scenery::Starfield,
+    };
+    vec![
+        theme("dark", "black", "white", None),
+        Theme {
+            scenery: Scenery::Clouds,
+            ..theme("light", "white", "black", None)
+        },
+        Theme {
+            fade: 0.5,
+            food: "\u{25c6}".to_string(),
+            font: format!("{PIXEL_FONT}, monospace"),
+            ..theme(
+                "retro",
+                "#0b140b",
+                "#7cff7c",
+                Some("rgba(124, 255, 124, 0.08)"),
+            )
+        },
+        Theme {
+            fade: 0.6,
+            food: "\u{25cf}".to_string(),
+            font: "Georgia, serif".to_string(),
+            scenery: Scenery::Clouds,
+            ..theme(
+                "ocean",
+                "#0a2540",
+                "#e0f2ff",
+                Some("rgba(255, 255, 255, 0.06)"),
+            )
+        },
+    ]
+}
+
+/// A built-in theme, or else a custom one, by name. `"auto"` is dark or
+/// light, whichever the system prefers.
+pub fn find(name: &str) -> Option<Theme> {
+    if name == AUTO {
+        return find(if PREFERS_DARK.get() { "dark" } else { "light" });
+    }
+    builtin()
+        .into_iter()
+        .find(|t| t.name == name)
+        .or_else(|| settings::with(|s| s.custom_themes.iter().find(|t| t.name == name).cloned()))
+}
+
+/// Saves `theme` among the custom themes and switches to it. It replaces
+/// a custom theme of the same name; one named after a built-in theme is
+/// saved as `custom-N` instead.
+pub fn save(mut theme: Theme) -> String {
+    settings::update(|s| {
+        if builtin().iter().any(|t| t.name == theme.name) {
+            let taken = |name: &str| s.custom_themes.iter().any(|t| t.name == name);
+            let n = (1..).find(|n| !taken(&format!("custom-{n}"))).unwrap_or(1);
+            theme.name = format!("custom-{n}");
+        }
+        s.custom_themes.retain(|t| t.name != theme.name);
+        s.custom_themes.push(theme.clone());
+        s.theme = theme.name.clone();
+    });
+    assets::font(&theme, js_sys::Date::now());
+    theme.name
+}
+
+/// The theme picked in the settings, in the pixel font if that is on.
+pub fn current() -> Theme {
+    let theme = settings::with(|s| find(&s.theme)).unwrap_or_else(|| builtin().swap_remove(0));
+    if settings::with(|s| s.pixel_font) {
+        Theme {
+            font: format!("{PIXEL_FONT}, monospace"),
+            ..theme
+        }
+    } else {
+        theme
+    }
+}
+
+/// Sets every theme's text in the pixel font, falling back to monospace
+/// until it loads or if it can't.
+#[wasm_bindgen]
+pub fn set_pixel_font(on: bool) {
+    settings::update(|s| s.pixel_font = on);
+    assets::font(&current(), js_sys::Date::now());
+}
+
+/// `"dark"`, `"light"`, `"retro"`, `"ocean"`, `"auto"` (dark or light as
+/// the system is) or the name of a custom theme.
+#[wasm_bindgen]
+pub fn set_theme(name: &str) -> Result<(), JsValue> {
+    let theme = find(name).ok_or_else(|| JsValue::from_str("unknown theme"))?;
+    assets::font(&theme, js_sys::Date::now());
+    settings::update(|s| s.theme = name.to_string());
+    Ok(())
+}
+
+/// Reads whether the system prefers dark colours and follows it as it
+/// changes, so the `"auto"` theme restyles the board straight away.
+pub fn watch_scheme() -> Result<(), JsValue> {
+    let Some(query) = web_sys::window()
+        .ok_or("no window")?
+        .match_media("(prefers-color-scheme: dark)")?
+    else {
+        return Ok(());
+    };
+    PREFERS_DARK.set(query.matches());
+    let changed = query.clone();
+    let closure = Closure::wrap(Box::new(move || {
+        PREFERS_DARK.set(changed.matches());
+        assets::font(&current(), js_sys::Date::now());
+    }) as Box<dyn FnMut()>);
+    query.add_event_listener_with_callback("change", closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    Ok(())
+}
+
+/// The current theme as JSON, to share or back up.
+#[wasm_bindgen]
+pub fn export_theme() -> String {
+    serde_json::to_string(&current()).unwrap_or_default()
+}
+
+/// Adds a theme from `export_theme()` to the custom themes and switches to
+/// it. Returns the name it was saved under.
+#[wasm_bindgen]
+pub fn import_theme(json: &str) -> Result<String, JsValue> {
+    let theme: Theme = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    Ok(save(theme))
+}
@@ -0,0 +1,72 @@
+use std::cmp::Reverse;
+
+use crate::core::board::Rules;
+use crate::core::handicap::Handicap;
+use crate::handicap;
+
+pub const MIN_PLAYERS: usize = 2;
+pub const MAX_PLAYERS: usize = 8;
+
+/// A pass-and-play session: every player gets one run on the same seed.
+pub struct Tournament {
+    pub seed: u64,
+    /// The rules picked when it started, kept for every player's run.
+    pub rules: Rules,
+    players: Vec<String>,
+    handicaps: Vec<Handicap>,
+    scores: Vec<Option<i32>>,
+}
+
+impl Tournament {
+    pub fn new(names: Vec<String>, seed: u64, rules: Rules) -> Option<Self> {
+        let players: Vec<String> = names
+            .into_iter()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect();
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&players.len()) {
+            return None;
+        }
+        Some(Self {
+            seed,
+            rules,
+            handicaps: (0..players.len()).map(handicap::lobby).collect(),
+            scores: vec![None; players.len()],
+            players,
+        })
+    }
+
+    /// The player whose run is next or in progress.
+    pub fn current(&self) -> Option<&str> {
+        let i = self.scores.iter().position(Option::is_none)?;
+        Some(&self.players[i])
+    }
+
+    pub fn current_handicap(&self) -> Handicap {
+        let i = self.scores.iter().position(Option::is_none);
+        i.map(|i| self.handicaps[i]).unwrap_or_default()
+    }
+
+    pub fn record(&mut self, score: i32) {
+        if let Some(slot) = self.scores.iter_mut().find(|s| s.is_none()) {
+            *slot = Some(score);
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.current().is_none()
+    }
+
+    /// Players who have had their run, best score first.
+    pub fn standings(&self) -> Vec<(&str, i32, Handicap)> {
+        let mut table: Vec<(&str, i32, Handicap)> = self
+            .players
+            .iter()
+            .zip(&self.scores)
+            .zip(&self.handicaps)
+            .filter_map(|((name, score), h)| Some((name.as_str(), (*score)?, *h)))
+            .collect();
+        table.sort_by_key(|&(_, score, _)| Reverse(score));
+        table
+    }
+}
@@ -0,0 +1,294 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use js_sys::{Array, Promise};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    BroadcastChannel, MessageEvent, RtcConfiguration, RtcDataChannel, RtcDataChannelEvent,
+    RtcIceGatheringState, RtcIceServer, RtcPeerConnection, RtcSdpType, RtcSessionDescriptionInit,
+    WebSocket,
+};
+
+use crate::net;
+
+const STUN: &str = "stun:stun.l.google.com:19302";
+// control frames for transports with no connection state of their own
+const HELLO: &str = "hello:";
+const BYE: &str = "bye";
+
+/// A pipe to the other player carrying one text frame per message.
+/// Implementations report what happens on it through `net::opened`,
+/// `net::received` and `net::closed`, tagged with the connection id they
+/// were built for.
+pub trait Transport {
+    /// Sends one frame, returning whether it went out.
+    fn send(&self, frame: &str) -> bool;
+    fn close(&self);
+}
+
+/// The handshake for relays and broadcast channels, which only carry
+/// frames. Both ends say hello with a random number until each has heard
+/// the other, the higher number hosts, and either side says bye on leaving.
+struct Greeting {
+    id: u32,
+    nonce: f64,
+    open: Cell<bool>,
+}
+
+impl Greeting {
+    fn new(id: u32) -> Self {
+        Self {
+            id,
+            nonce: js_sys::Math::random(),
+            open: Cell::new(false),
+        }
+    }
+
+    fn hello(&self) -> String {
+        format!("{HELLO}{}", self.nonce)
+    }
+
+    fn handle(&self, frame: String, reply: impl Fn(&str)) {
+        if let Some(theirs) = frame.strip_prefix(HELLO) {
+            let Ok(theirs) = theirs.parse::<f64>() else {
+                return;
+            };
+            if !self.open.get() {
+                self.open.set(true);
+                reply(&self.hello());
+                net::set_host(self.id, self.nonce > theirs);
+                net::opened(self.id);
+            }
+        } else if frame == BYE {
+            net::closed(self.id);
+        } else {
+            net::received(self.id, frame);
+        }
+    }
+}
+
+fn on_text(handle: impl Fn(String) + 'static) -> Closure<dyn FnMut(MessageEvent)> {
+    Closure::wrap(Box::new(move |event: MessageEvent| {
+        if let Some(text) = event.data().as_string() {
+            handle(text);
+        }
+    }) as Box<dyn FnMut(_)>)
+}
+
+/// A WebRTC data channel straight to the other browser.
+struct DataChannel {
+    pc: RtcPeerConnection,
+    // filled in on the joining side once the host's channel arrives
+    channel: Rc<RefCell<Option<RtcDataChannel>>>,
+}
+
+impl Transport for DataChannel {
+    fn send(&self, frame: &str) -> bool {
+        self.channel
+            .borrow()
+            .as_ref()
+            .is_some_and(|c| c.send_with_str(frame).is_ok())
+    }
+
+    fn close(&self) {
+        self.pc.close();
+    }
+}
+
+thread_local! {
+    // the hosted connection still waiting for the guest's answer
+    static OFFERED: RefCell<Option<RtcPeerConnection>> = const { RefCell::new(None) };
+}
+
+/// Starts hosting a peer-to-peer match and resolves to the offer text the
+/// other player passes to `p2p_join`.
+#[wasm_bindgen]
+pub async fn p2p_host() -> Result<String, JsValue> {
+    let pc = rtc_connection()?;
+    let channel = pc.create_data_channel("snake");
+    net::connect(true, |id| {
+        wire_channel(id, &channel);
+        DataChannel {
+            pc: pc.clone(),
+            channel: Rc::new(RefCell::new(Some(channel))),
+        }
+    });
+    OFFERED.with(|o| o.replace(Some(pc.clone())));
+    let offer = JsFuture::from(pc.create_offer()).await?;
+    JsFuture::from(pc.set_local_description(offer.unchecked_ref())).await?;
+    gathered(&pc).await
+}
+
+/// Joins a hosted match from its offer text and resolves to the answer text
+/// the host passes to `p2p_accept`.
+#[wasm_bindgen]
+pub async fn p2p_join(offer: String) -> Result<String, JsValue> {
+    let pc = rtc_connection()?;
+    net::connect(false, |id| {
+        let slot = Rc::new(RefCell::new(None));
+        let filled = slot.clone();
+        let on_channel = Closure::wrap(Box::new(move |event: RtcDataChannelEvent| {
+            let channel = event.channel();
+            wire_channel(id, &channel);
+            filled.replace(Some(channel));
+        }) as Box<dyn FnMut(_)>);
+        pc.set_ondatachannel(Some(on_channel.as_ref().unchecked_ref()));
+        on_channel.forget();
+        DataChannel {
+            pc: pc.clone(),
+            channel: slot,
+        }
+    });
+    let remote = RtcSessionDescriptionInit::new(RtcSdpType::Offer);
+    remote.set_sdp(&offer);
+    JsFuture::from(pc.set_remote_description(&remote)).await?;
+    let answer = JsFuture::from(pc.create_answer()).await?;
+    JsFuture::from(pc.set_local_description(answer.unchecked_ref())).await?;
+    gathered(&pc).await
+}
+
+/// Completes the handshake on the host with the joining player's answer.
+#[wasm_bindgen]
+pub async fn p2p_accept(answer: String) -> Result<(), JsValue> {
+    let pc = OFFERED
+        .with(|o| o.borrow().clone())
+        .ok_or_else(|| JsValue::from_str("no match is being hosted"))?;
+    let remote = RtcSessionDescriptionInit::new(RtcSdpType::Answer);
+    remote.set_sdp(&answer);
+    JsFuture::from(pc.set_remote_description(&remote)).await?;
+    Ok(())
+}
+
+fn rtc_connection() -> Result<RtcPeerConnection, JsValue> {
+    let server = RtcIceServer::new();
+    server.set_urls_str(STUN);
+    let config = RtcConfiguration::new();
+    config.set_ice_servers(&Array::of1(&server));
+    RtcPeerConnection::new_with_configuration(&config)
+}
+
+fn wire_channel(id: u32, channel: &RtcDataChannel) {
+    let on_open = Closure::wrap(Box::new(move || net::opened(id)) as Box<dyn FnMut()>);
+    channel.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+    on_open.forget();
+
+    let on_message = on_text(move |text| net::received(id, text));
+    channel.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    on_message.forget();
+
+    let on_close = Closure::wrap(Box::new(move || net::closed(id)) as Box<dyn FnMut()>);
+    channel.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+    on_close.forget();
+}
+
+// Waits for ICE gathering to finish so the description carries every
+// candidate and can be exchanged in one copy-paste.
+async fn gathered(pc: &RtcPeerConnection) -> Result<String, JsValue> {
+    if pc.ice_gathering_state() != RtcIceGatheringState::Complete {
+        let done = Promise::new(&mut |resolve, _| {
+            let watched = pc.clone();
+            let closure = Closure::wrap(Box::new(move || {
+                if watched.ice_gathering_state() == RtcIceGatheringState::Complete {
+                    let _ = resolve.call0(&JsValue::NULL);
+                }
+            }) as Box<dyn FnMut()>);
+            pc.set_onicegatheringstatechange(Some(closure.as_ref().unchecked_ref()));
+            closure.forget();
+        });
+        JsFuture::from(done).await?;
+    }
+    Ok(pc
+        .local_description()
+        .map(|desc| desc.sdp())
+        .unwrap_or_default())
+}
+
+/// A WebSocket to a relay server that forwards each frame to the other
+/// client.
+struct Relay {
+    socket: WebSocket,
+}
+
+impl Transport for Relay {
+    fn send(&self, frame: &str) -> bool {
+        self.socket.send_with_str(frame).is_ok()
+    }
+
+    fn close(&self) {
+        let _ = self.socket.send_with_str(BYE);
+        let _ = self.socket.close();
+    }
+}
+
+/// Plays over a WebSocket relay at `url`, which must forward every message
+/// to the other connected client.
+#[wasm_bindgen]
+pub fn ws_connect(url: &str) -> Result<(), JsValue> {
+    let socket = WebSocket::new(url)?;
+    net::connect(false, |id| {
+        let greeting = Rc::new(Greeting::new(id));
+
+        let greeter = socket.clone();
+        let hello = greeting.hello();
+        let on_open = Closure::wrap(Box::new(move || {
+            let _ = greeter.send_with_str(&hello);
+        }) as Box<dyn FnMut()>);
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        on_open.forget();
+
+        let replier = socket.clone();
+        let on_message = on_text(move |text| {
+            greeting.handle(text, |frame| {
+                let _ = replier.send_with_str(frame);
+            })
+        });
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        on_message.forget();
+
+        let on_close = Closure::wrap(Box::new(move || net::closed(id)) as Box<dyn FnMut()>);
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+        on_close.forget();
+
+        Relay { socket }
+    });
+    Ok(())
+}
+
+/// A `BroadcastChannel` to another tab of the same site.
+struct Tabs {
+    channel: BroadcastChannel,
+}
+
+impl Transport for Tabs {
+    fn send(&self, frame: &str) -> bool {
+        self.channel.post_message(&JsValue::from_str(frame)).is_ok()
+    }
+
+    fn close(&self) {
+        let _ = self.channel.post_message(&JsValue::from_str(BYE));
+        self.channel.close();
+    }
+}
+
+/// Plays against another tab in this browser that joined the same `room`,
+/// with no server at all.
+#[wasm_bindgen]
+pub fn tab_connect(room: &str) -> Result<(), JsValue> {
+    let channel = BroadcastChannel::new(&format!("snake_game.{room}"))?;
+    net::connect(false, |id| {
+        let greeting = Greeting::new(id);
+        let _ = channel.post_message(&JsValue::from_str(&greeting.hello()));
+        let replier = channel.clone();
+        let on_message = on_text(move |text| {
+            greeting.handle(text, |frame| {
+                let _ = replier.post_message(&JsValue::from_str(frame));
+            })
+        });
+        channel.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        on_message.forget();
+        Tabs { channel }
+    });
+    Ok(())
+}
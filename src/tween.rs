@@ -0,0 +1,71 @@
+use std::f64::consts::PI;
+
+/// How a tween gets from its start to its end.
+#[derive(Clone, Copy)]
+pub enum Ease {
+    /// Slow at both ends, like something breathing.
+    InOutSine,
+    /// Quick off the mark, slowing into the end.
+    OutQuad,
+}
+
+impl Ease {
+    fn apply(self, t: f64) -> f64 {
+        match self {
+            Ease::InOutSine => (1.0 - (PI * t).cos()) / 2.0,
+            Ease::OutQuad => 1.0 - (1.0 - t) * (1.0 - t),
+        }
+    }
+}
+
+/// A value eased from `from` to `to` over `ms` milliseconds. It runs only
+/// on the clock it is given, so it can animate the screen without touching
+/// the game.
+#[derive(Clone, Copy)]
+pub struct Tween {
+    pub from: f64,
+    pub to: f64,
+    pub ms: f64,
+    pub ease: Ease,
+    /// Whether it swings back to `from` and starts again, resting at `from`
+    /// for `rest` milliseconds between swings.
+    pub repeat: Option<f64>,
+}
+
+impl Tween {
+    pub const fn new(from: f64, to: f64, ms: f64, ease: Ease) -> Self {
+        Self {
+            from,
+            to,
+            ms,
+            ease,
+            repeat: None,
+        }
+    }
+
+    /// Swings there and back forever, with `rest` ms at `from` in between.
+    pub const fn swinging(self, rest: f64) -> Self {
+        Self {
+            repeat: Some(rest),
+            ..self
+        }
+    }
+
+    /// The value `elapsed` ms after the tween started.
+    pub fn at(&self, elapsed: f64) -> f64 {
+        let t = match self.repeat {
+            None => (elapsed / self.ms).clamp(0.0, 1.0),
+            Some(rest) => {
+                let phase = elapsed.max(0.0) % (2.0 * self.ms + rest);
+                if phase < self.ms {
+                    phase / self.ms
+                } else if phase < 2.0 * self.ms {
+                    2.0 - phase / self.ms
+                } else {
+                    0.0
+                }
+            }
+        };
+        self.from + (self.to - self.from) * self.ease.apply(t)
+    }
+}
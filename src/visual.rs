@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, Document, HtmlButtonElement, HtmlCanvasElement};
+
+use crate::backdrop::Backdrop;
+use crate::core::board::{Event, Rules};
+use crate::core::rng::Rng;
+use crate::core::{HEIGHT, WIDTH};
+use crate::layout::Layout;
+use crate::{bus, solo_snake, AppState, Game};
+
+// small enough to render quickly, large enough for the HUD text
+const CELL: f64 = 16.0;
+// the hash compares each of 8 rows of 9 averaged blocks with its neighbour
+const HASH_W: usize = 9;
+const HASH_H: usize = 8;
+
+/// A scripted single-player run to render. `inputs` holds one letter per
+/// tick, `U`, `D`, `L` or `R` to turn and anything else to carry on.
+#[derive(Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    pub seed: u64,
+    /// Rules in the replay format, such as `"walls+tiny"`.
+    pub rules: String,
+    pub inputs: String,
+}
+
+#[derive(Serialize)]
+struct Capture {
+    name: String,
+    ticks: u32,
+    width: u32,
+    height: u32,
+    /// 64-bit difference hash of the frame, in hex.
+    hash: String,
+}
+
+fn builtin() -> Vec<Scenario> {
+    let scenario = |name: &str, seed, rules: &str, inputs: &str| Scenario {
+        name: name.to_string(),
+        seed,
+        rules: rules.to_string(),
+        inputs: inputs.to_string(),
+    };
+    vec![
+        scenario("start", 1, "wrap", ""),
+        scenario("turns", 2, "wrap", "....D...L...U..R......"),
+        scenario("wrap_edge", 3, "wrap", ".........."),
+        scenario("walls", 4, "walls", "..D..R..U"),
+        scenario("tiny", 5, "walls+tiny", "...D"),
+        scenario("poison", 6, "wrap+poison", "....U...."),
+        scenario("rotate", 7, "wrap+rotate", &".".repeat(60)),
+    ]
+}
+
+/// Renders each scenario on a canvas of its own and reports a perceptual
+/// hash of the final frame as JSON, `[{name, ticks, width, height, hash}]`.
+/// Hashes of the same scenario from two browsers, or two builds, can be
+/// compared bit by bit; a few differing bits is anti-aliasing, many is
+/// drift. Takes a JSON array of scenarios, or runs the built-in set.
+/// Frames follow the saved theme, palette and background settings, so
+/// compare reports made with the same settings.
+#[wasm_bindgen]
+pub fn visual_report(scenarios: Option<String>) -> Result<String, JsValue> {
+    let scenarios = match scenarios {
+        Some(json) => serde_json::from_str(&json).map_err(|e| e.to_string())?,
+        None => builtin(),
+    };
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or("no document")?;
+    let captures = scenarios
+        .iter()
+        .map(|s| capture(&document, s))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(serde_json::to_string(&captures).unwrap_or_default())
+}
+
+fn capture(document: &Document, scenario: &Scenario) -> Result<Capture, JsValue> {
+    let rules = Rules::decode(&scenario.rules).ok_or("invalid rules")?;
+    let canvas: HtmlCanvasElement = document.create_element("canvas")?.dyn_into()?;
+    let (width, height) = ((WIDTH as f64 * CELL) as u32, (HEIGHT as f64 * CELL) as u32);
+    canvas.set_width(width);
+    canvas.set_height(height);
+    let ctx = canvas
+        .get_context("2d")?
+        .ok_or("no 2d context")?
+        .dyn_into::<CanvasRenderingContext2d>()?;
+    // never shown; the game only toggles its style
+    let restart_btn: HtmlButtonElement = document.create_element("button")?.dyn_into()?;
+    let layout = Layout {
+        cell: CELL,
+        scale: 1.0,
+        usable: true,
+    };
+    let mut game = Game::new(ctx.clone(), Backdrop::new(document)?, restart_btn, &layout);
+    game.best = None;
+    game.rng = Rng::new(scenario.seed);
+    game.begin(vec![solo_snake()], 0, rules);
+    game.state = AppState::Playing;
+    // keeps a scenario that crashes out of today's games and the high scores
+    game.unrecorded = true;
+    for c in scenario.inputs.chars() {
+        if game.board.over {
+            break;
+        }
+        let dir = match c {
+            'U' => Some((0, -1)),
+            'D' => Some((0, 1)),
+            'L' => Some((-1, 0)),
+            'R' => Some((1, 0)),
+            _ => None,
+        };
+        if let Some(dir) = dir {
+            game.dispatch(Event::TurnRequested { snake: 0, dir });
+        }
+        game.tick();
+    }
+    // the board where it is, rather than gliding in from the last tick
+    game.prev_snakes.clear();
+    game.draw_at(0.0)?;
+    // the scenario's events aren't meant for the game on the page
+    bus::drain();
+    let pixels = ctx
+        .get_image_data(0.0, 0.0, f64::from(width), f64::from(height))?
+        .data();
+    Ok(Capture {
+        name: scenario.name.clone(),
+        ticks: game.board.tick,
+        width,
+        height,
+        hash: format!("{:016x}", dhash(&pixels.0, width as usize, height as usize)),
+    })
+}
+
+// Averages the frame's brightness over a 9x8 grid and sets a bit for each
+// block brighter than the one to its right.
+fn dhash(rgba: &[u8], width: usize, height: usize) -> u64 {
+    let mut grid = [[0.0; HASH_W]; HASH_H];
+    for (gy, row) in grid.iter_mut().enumerate() {
+        let (y0, y1) = (gy * height / HASH_H, (gy + 1) * height / HASH_H);
+        for (gx, block) in row.iter_mut().enumerate() {
+            let (x0, x1) = (gx * width / HASH_W, (gx + 1) * width / HASH_W);
+            let mut sum = 0.0;
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    let i = (y * width + x) * 4;
+                    sum += 0.299 * f64::from(rgba[i])
+                        + 0.587 * f64::from(rgba[i + 1])
+                        + 0.114 * f64::from(rgba[i + 2]);
+                }
+            }
+            *block = sum / ((x1 - x0) * (y1 - y0)).max(1) as f64;
+        }
+    }
+    let mut hash = 0;
+    for row in &grid {
+        for pair in row.windows(2) {
+            hash = hash << 1 | u64::from(pair[0] > pair[1]);
+        }
+    }
+    hash
+}
@@ -0,0 +1,222 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+use web_sys::{
+    CanvasRenderingContext2d, Document, HtmlCanvasElement, WebGl2RenderingContext as GL,
+    WebGlBuffer, WebGlProgram, WebGlShader, WebGlUniformLocation, WebGlVertexArrayObject,
+};
+
+use crate::renderer::{Quad, Renderer};
+
+// Each quad is one instance of a unit square, stretched over its rect.
+const VERTEX: &str = "#version 300 es
+layout(location = 0) in vec2 corner;
+layout(location = 1) in vec4 rect;
+layout(location = 2) in vec4 colour;
+uniform vec2 size;
+out vec4 tint;
+void main() {
+    vec2 at = (rect.xy + corner * rect.zw) / size * 2.0 - 1.0;
+    gl_Position = vec4(at.x, -at.y, 0.0, 1.0);
+    tint = colour;
+}
+";
+
+// premultiplied, as the 2D canvas composites it
+const FRAGMENT: &str = "#version 300 es
+precision mediump float;
+in vec4 tint;
+out vec4 colour;
+void main() {
+    colour = vec4(tint.rgb * tint.a, tint.a);
+}
+";
+
+// floats per instance: the rect, then the colour
+const STRIDE: i32 = 8;
+
+/// Draws the board's cells as instanced quads on a WebGL canvas of its
+/// own, then copies that onto the game's canvas in one go.
+pub struct Gl {
+    canvas: HtmlCanvasElement,
+    gl: GL,
+    program: WebGlProgram,
+    vao: WebGlVertexArrayObject,
+    instances: WebGlBuffer,
+    size: WebGlUniformLocation,
+    // the board's size in CSS pixels
+    board: Cell<(f64, f64)>,
+    // CSS colours, resolved to RGBA the first time they are drawn
+    colours: RefCell<HashMap<String, [f32; 4]>>,
+}
+
+impl Gl {
+    pub fn new(document: &Document) -> Result<Self, JsValue> {
+        let canvas: HtmlCanvasElement = document.create_element("canvas")?.dyn_into()?;
+        let gl: GL = canvas
+            .get_context("webgl2")?
+            .ok_or("no webgl2 context")?
+            .dyn_into()?;
+        let program = link(
+            &gl,
+            &compile(&gl, GL::VERTEX_SHADER, VERTEX)?,
+            &compile(&gl, GL::FRAGMENT_SHADER, FRAGMENT)?,
+        )?;
+        let size = gl
+            .get_uniform_location(&program, "size")
+            .ok_or("no size uniform")?;
+        let vao = gl.create_vertex_array().ok_or("no vertex array")?;
+        gl.bind_vertex_array(Some(&vao));
+        // the unit square, as two triangles
+        let corners = gl.create_buffer().ok_or("no buffer")?;
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&corners));
+        let square: [f32; 12] = [0., 0., 1., 0., 0., 1., 0., 1., 1., 0., 1., 1.];
+        gl.buffer_data_with_array_buffer_view(
+            GL::ARRAY_BUFFER,
+            &js_sys::Float32Array::from(&square[..]),
+            GL::STATIC_DRAW,
+        );
+        gl.enable_vertex_attrib_array(0);
+        gl.vertex_attrib_pointer_with_i32(0, 2, GL::FLOAT, false, 0, 0);
+        let instances = gl.create_buffer().ok_or("no buffer")?;
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&instances));
+        for (location, offset) in [(1, 0), (2, 4)] {
+            gl.enable_vertex_attrib_array(location);
+            gl.vertex_attrib_pointer_with_i32(
+                location,
+                4,
+                GL::FLOAT,
+                false,
+                STRIDE * 4,
+                offset * 4,
+            );
+            gl.vertex_attrib_divisor(location, 1);
+        }
+        gl.bind_vertex_array(None);
+        gl.enable(GL::BLEND);
+        gl.blend_func(GL::ONE, GL::ONE_MINUS_SRC_ALPHA);
+        Ok(Self {
+            canvas,
+            gl,
+            program,
+            vao,
+            instances,
+            size,
+            board: Cell::new((0.0, 0.0)),
+            colours: RefCell::new(HashMap::new()),
+        })
+    }
+
+    // The RGBA for a CSS colour, which `target` turns into a form this can
+    // read: `#rrggbb`, or `rgba(r, g, b, a)` when it isn't opaque.
+    fn colour(&self, target: &CanvasRenderingContext2d, css: &str) -> [f32; 4] {
+        if let Some(&rgba) = self.colours.borrow().get(css) {
+            return rgba;
+        }
+        target.set_fill_style_str("#000000");
+        target.set_fill_style_str(css);
+        let rgba = target
+            .fill_style()
+            .as_string()
+            .and_then(|s| parse(&s))
+            .unwrap_or([0.0, 0.0, 0.0, 1.0]);
+        self.colours.borrow_mut().insert(css.to_string(), rgba);
+        rgba
+    }
+}
+
+impl Renderer for Gl {
+    fn resize(&self, (width, height): (f64, f64), scale: f64) {
+        self.canvas.set_width((width * scale).round() as u32);
+        self.canvas.set_height((height * scale).round() as u32);
+        self.board.set((width, height));
+    }
+
+    fn draw(&self, target: &CanvasRenderingContext2d, quads: &[Quad]) -> Result<(), JsValue> {
+        let mut data = Vec::with_capacity(quads.len() * STRIDE as usize);
+        for quad in quads {
+            let (x, y, w, h) = quad.rect;
+            let [r, g, b, a] = self.colour(target, quad.colour);
+            data.extend([x as f32, y as f32, w as f32, h as f32, r, g, b]);
+            data.push(a * quad.alpha as f32);
+        }
+        let gl = &self.gl;
+        let (width, height) = self.board.get();
+        gl.viewport(
+            0,
+            0,
+            self.canvas.width() as i32,
+            self.canvas.height() as i32,
+        );
+        gl.clear_color(0.0, 0.0, 0.0, 0.0);
+        gl.clear(GL::COLOR_BUFFER_BIT);
+        gl.use_program(Some(&self.program));
+        gl.uniform2f(Some(&self.size), width as f32, height as f32);
+        gl.bind_vertex_array(Some(&self.vao));
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&self.instances));
+        gl.buffer_data_with_array_buffer_view(
+            GL::ARRAY_BUFFER,
+            &js_sys::Float32Array::from(&data[..]),
+            GL::STREAM_DRAW,
+        );
+        gl.draw_arrays_instanced(GL::TRIANGLES, 0, 6, quads.len() as i32);
+        gl.bind_vertex_array(None);
+        target.draw_image_with_html_canvas_element_and_dw_and_dh(
+            &self.canvas,
+            0.0,
+            0.0,
+            width,
+            height,
+        )
+    }
+}
+
+fn compile(gl: &GL, kind: u32, source: &str) -> Result<WebGlShader, JsValue> {
+    let shader = gl.create_shader(kind).ok_or("no shader")?;
+    gl.shader_source(&shader, source);
+    gl.compile_shader(&shader);
+    if gl
+        .get_shader_parameter(&shader, GL::COMPILE_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(shader)
+    } else {
+        Err(gl.get_shader_info_log(&shader).unwrap_or_default().into())
+    }
+}
+
+fn link(gl: &GL, vertex: &WebGlShader, fragment: &WebGlShader) -> Result<WebGlProgram, JsValue> {
+    let program = gl.create_program().ok_or("no program")?;
+    gl.attach_shader(&program, vertex);
+    gl.attach_shader(&program, fragment);
+    gl.link_program(&program);
+    if gl
+        .get_program_parameter(&program, GL::LINK_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(program)
+    } else {
+        Err(gl.get_program_info_log(&program).unwrap_or_default().into())
+    }
+}
+
+// Reads a colour as the 2D canvas gives it back.
+fn parse(css: &str) -> Option<[f32; 4]> {
+    if let Some(hex) = css.strip_prefix('#') {
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        let [r, g, b] = [channel(0)?, channel(2)?, channel(4)?].map(|c| f32::from(c) / 255.0);
+        return Some([r, g, b, 1.0]);
+    }
+    let inner = css.strip_prefix("rgba(")?.strip_suffix(')')?;
+    let mut parts = inner.split(',').map(|p| p.trim().parse::<f32>().ok());
+    let (r, g, b, a) = (
+        parts.next()??,
+        parts.next()??,
+        parts.next()??,
+        parts.next()??,
+    );
+    Some([r / 255.0, g / 255.0, b / 255.0, a])
+}